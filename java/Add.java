@@ -39,17 +39,17 @@ public class Add {
 //    return i + 1_000_000;
 //  }
 //
-//  public static int addMany(int a, int b, int c, int d, int f, int e) {
-//    return a + b + c+ d+ e+ f;
-//  }
-//
-//  public static int doStuff() {
-//    int i = 0;
-//    while (i < 10) {
-//      i++;
-//    }
-//    return i;
-//  }
+  public static int addMany(int a, int b, int c, int d, int f, int e) {
+    return a + b + c+ d+ e+ f;
+  }
+
+  public static int doStuff() {
+    int i = 0;
+    while (i < 10) {
+      i++;
+    }
+    return i;
+  }
 
   public int result() {
     return this.args[0] + this.args[1];
@@ -0,0 +1,50 @@
+public class ArrayChecks {
+
+  public static int load(int index) {
+    int[] a = new int[2];
+    return a[index];
+  }
+
+  public static void store(int index) {
+    String[] a = new String[2];
+    a[index] = "x";
+  }
+
+  public static int newInts(int size) {
+    return new int[size].length;
+  }
+
+  public static int newStrings(int size) {
+    return new String[size].length;
+  }
+
+  public static int newGrid(int rows, int columns) {
+    return new int[rows][columns].length;
+  }
+
+  public static int loadNull() {
+    int[] a = null;
+    return a[0];
+  }
+
+  public static void storeNull() {
+    boolean[] a = null;
+    a[0] = true;
+  }
+
+  public static Object loadNullObject() {
+    Object[] a = null;
+    return a[0];
+  }
+
+  public static int lengthNull() {
+    long[] a = null;
+    return a.length;
+  }
+
+  // The index is checked before the type of the stored value
+  public static void storeOutOfBounds() {
+    Object[] a = new String[1];
+    a[1] = Integer.valueOf(1);
+  }
+}
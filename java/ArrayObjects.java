@@ -0,0 +1,59 @@
+// Methods of java.lang.Object called on arrays
+public class ArrayObjects {
+
+  public static int hashCodeOf(Object[] a) {
+    return a.hashCode();
+  }
+
+  public static String concat(Object[] a) {
+    return "" + a;
+  }
+
+  public static String toStringOf(Object a) {
+    return a.toString();
+  }
+
+  public static int cloneInts() {
+    int[] a = {1, 2, 3};
+    int[] b = a.clone();
+    b[0] = 10;
+    return a[0] + b[0] + (a != b ? 100 : 0) + b.length * 1000;
+  }
+
+  // The copy is shallow, its elements are the same objects
+  public static int cloneStrings(String[] a) {
+    String[] b = a.clone();
+    return b != a && b[0] == a[0] ? b.length : -1;
+  }
+
+  public static int cloneGrid() {
+    int[][] grid = new int[2][3];
+    int[][] copy = grid.clone();
+    return (copy[1] == grid[1] ? 1 : 0) + (copy instanceof int[][] ? 10 : 0);
+  }
+
+  public static int clonePoint() throws CloneNotSupportedException {
+    ArrayObjectsPoint p = new ArrayObjectsPoint();
+    p.x = 5;
+    ArrayObjectsPoint copy = p.copy();
+    copy.x = 7;
+    return p.x * 10 + copy.x;
+  }
+
+  public static String cloneUncloneable() {
+    try {
+      new ArrayObjects().clone();
+      return "cloned";
+    } catch (CloneNotSupportedException e) {
+      return e.getMessage();
+    }
+  }
+}
+
+class ArrayObjectsPoint implements Cloneable {
+  int x;
+
+  ArrayObjectsPoint copy() throws CloneNotSupportedException {
+    return (ArrayObjectsPoint) clone();
+  }
+}
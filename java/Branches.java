@@ -0,0 +1,98 @@
+public class Branches {
+
+  public static int sumTo(int n) {
+    int sum = 0;
+    for (int i = 1; i <= n; i++) {
+      sum += i;
+    }
+    return sum;
+  }
+
+  // The condition of a do-while loop branches backwards
+  public static int countDown(int n) {
+    int steps = 0;
+    do {
+      n--;
+      steps++;
+    } while (n > 0);
+    return steps;
+  }
+
+  public static int nested(int n) {
+    int count = 0;
+    for (int i = 0; i < n; i++) {
+      for (int j = 0; j < i; j++) {
+        if (j % 2 == 0) {
+          continue;
+        }
+        count++;
+      }
+    }
+    return count;
+  }
+
+  public static int max(long a, long b) {
+    return a > b ? 1 : 2;
+  }
+
+  public static int classify(double d) {
+    if (d < 0) {
+      return -1;
+    } else if (d > 0) {
+      return 1;
+    }
+    return 0;
+  }
+
+  public static int isNull(Object o) {
+    return o == null ? 1 : 0;
+  }
+
+  // Dense cases compile to tableswitch
+  public static int dense(int i) {
+    switch (i) {
+      case -1:
+        return 10;
+      case 0:
+        return 20;
+      case 1:
+      case 2:
+        return 30;
+      case 4:
+        return 40;
+      default:
+        return 0;
+    }
+  }
+
+  // Sparse cases compile to lookupswitch
+  public static int sparse(int i) {
+    switch (i) {
+      case -1000000:
+        return 1;
+      case 7:
+        return 2;
+      case 1000:
+        return 3;
+      case Integer.MAX_VALUE:
+        return 4;
+      default:
+        return 0;
+    }
+  }
+
+  // Falls through from one case into the next
+  public static int fallThrough(int i) {
+    int result = 0;
+    switch (i) {
+      case 1:
+        result += 1;
+      case 2:
+        result += 2;
+        break;
+      case 3:
+        result += 3;
+    }
+    return result;
+  }
+}
@@ -0,0 +1,78 @@
+public class Casts {
+
+  static Object make(int kind) {
+    switch (kind) {
+      case 1: return new CastsBase();
+      case 2: return new CastsChild();
+      case 3: return new CastsChild[1];
+      case 4: return new CastsBase[1][1];
+      case 5: return new int[1];
+      case 6: return new int[1][1];
+      case 7: return "text";
+      default: return null;
+    }
+  }
+
+  // One bit per type the object is an instance of
+  public static int instances(int kind) {
+    Object o = make(kind);
+    int result = 0;
+    result |= o instanceof CastsBase ? 1 : 0;
+    result |= o instanceof CastsChild ? 2 : 0;
+    result |= o instanceof CastsTag ? 4 : 0;
+    result |= o instanceof Object ? 8 : 0;
+    result |= o instanceof Object[] ? 16 : 0;
+    result |= o instanceof CastsBase[] ? 32 : 0;
+    result |= o instanceof CastsTag[] ? 64 : 0;
+    result |= o instanceof Cloneable ? 128 : 0;
+    result |= o instanceof java.io.Serializable ? 256 : 0;
+    result |= o instanceof int[] ? 512 : 0;
+    result |= o instanceof Object[][] ? 1024 : 0;
+    result |= o instanceof String ? 2048 : 0;
+    return result;
+  }
+
+  // One bit per target of cast the object can be cast to
+  public static int casts(int kind) {
+    int result = 0;
+    for (int target = 0; target < 10; target++) {
+      try {
+        cast(kind, target);
+        result |= 1 << target;
+      } catch (ClassCastException e) {
+        // not castable to the target
+      }
+    }
+    return result;
+  }
+
+  // Returns the target if the cast succeeds
+  public static int cast(int kind, int target) {
+    Object o = make(kind);
+    switch (target) {
+      case 0: CastsBase base = (CastsBase) o; break;
+      case 1: CastsChild child = (CastsChild) o; break;
+      case 2: CastsTag tag = (CastsTag) o; break;
+      case 3: Object[] objects = (Object[]) o; break;
+      case 4: CastsBase[] bases = (CastsBase[]) o; break;
+      case 5: CastsChild[] children = (CastsChild[]) o; break;
+      case 6: Cloneable cloneable = (Cloneable) o; break;
+      case 7: String string = (String) o; break;
+      case 8: int[] ints = (int[]) o; break;
+      default: Object[][] grid = (Object[][]) o; break;
+    }
+    return target;
+  }
+}
+
+interface CastsTag {
+}
+
+interface CastsMarker extends CastsTag {
+}
+
+class CastsBase {
+}
+
+class CastsChild extends CastsBase implements CastsMarker {
+}
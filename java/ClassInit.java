@@ -0,0 +1,105 @@
+// Class initializers completing abruptly, the classes are left erroneous
+public class ClassInit {
+
+  // The exception is wrapped the first time, later accesses find the class erroneous
+  public static int clinitFails() {
+    int result = 0;
+    try {
+      result += ClassInitFailing.value;
+    } catch (ExceptionInInitializerError e) {
+      result += e.getCause() instanceof ArithmeticException ? 10 : 1;
+    }
+    try {
+      result += ClassInitFailing.value;
+    } catch (NoClassDefFoundError e) {
+      result += 100;
+    }
+    return result;
+  }
+
+  public static String thrownMessages() {
+    String first;
+    try {
+      first = "" + ClassInitThrowing.value;
+    } catch (ExceptionInInitializerError e) {
+      first = e.getCause().getMessage();
+    }
+    try {
+      return first + ", " + ClassInitThrowing.value;
+    } catch (NoClassDefFoundError e) {
+      return first + ", " + e.getMessage();
+    }
+  }
+
+  // Errors are not wrapped
+  public static String errorMessage() {
+    try {
+      return "" + ClassInitError.value;
+    } catch (Error e) {
+      return e.getMessage();
+    }
+  }
+
+  // A class whose super class fails to initialize is erroneous as well
+  public static String childMessages() {
+    String first;
+    try {
+      first = "" + ClassInitChild.value;
+    } catch (ExceptionInInitializerError e) {
+      first = e.getCause().getMessage();
+    }
+    try {
+      return first + ", " + ClassInitChild.value;
+    } catch (NoClassDefFoundError e) {
+      return first + ", " + e.getMessage();
+    }
+  }
+
+  public static int uncaught() {
+    return ClassInitUncaught.value;
+  }
+}
+
+class ClassInitFailing {
+  static int value = 1 / zero();
+
+  static int zero() {
+    return 0;
+  }
+}
+
+class ClassInitThrowing {
+  static int value = fail("no value");
+
+  static int fail(String message) {
+    throw new IllegalStateException(message);
+  }
+}
+
+class ClassInitError {
+  static int value = fail();
+
+  static int fail() {
+    throw new Error("fatal");
+  }
+}
+
+class ClassInitParent {
+  static int parentValue = fail();
+
+  static int fail() {
+    throw new IllegalStateException("no parent");
+  }
+}
+
+class ClassInitChild extends ClassInitParent {
+  static int value = 3;
+}
+
+class ClassInitUncaught {
+  static int value = 1 / zero();
+
+  static int zero() {
+    return 0;
+  }
+}
@@ -0,0 +1,39 @@
+public class Concat {
+
+  public static String primitives(int i, long l, float f, double d, char c, boolean b) {
+    return "i=" + i + " l=" + l + " f=" + f + " d=" + d + " c=" + c + " b=" + b;
+  }
+
+  public static String strings(String greeting, String name) {
+    return greeting + ", " + name + "!";
+  }
+
+  public static String objects() {
+    ConcatPoint p = new ConcatPoint(3, 4);
+    Object nothing = null;
+    return "p=" + p + " nothing=" + nothing;
+  }
+
+  public static String identity() {
+    return "" + new Concat();
+  }
+
+  // Literal \u0001 and \u0002 characters are passed to the bootstrap method as constants
+  public static String tags(int i) {
+    return "\u0001" + i + "\u0002";
+  }
+}
+
+class ConcatPoint {
+  private final int x;
+  private final int y;
+
+  ConcatPoint(int x, int y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  public String toString() {
+    return "(" + x + ", " + y + ")";
+  }
+}
@@ -0,0 +1,61 @@
+import java.lang.invoke.MethodHandles;
+
+// Bootstrap methods of the dynamically-computed constants in Condy.class, which is generated by gen/GenCondy.java
+public class CondyBootstraps {
+
+  static int calls;
+
+  public static Object answer(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    calls = calls + 1;
+    return Integer.valueOf(42);
+  }
+
+  public static int sum(MethodHandles.Lookup lookup, String name, Class<?> type, int a, int b) {
+    return a + b;
+  }
+
+  public static Object name(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    return name;
+  }
+
+  public static Object lookupClass(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    return lookup.lookupClass().getName();
+  }
+
+  public static Object failing(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    calls = calls + 1;
+    Object nothing = null;
+    return nothing.toString();
+  }
+
+  public static Object missingClass(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    calls = calls + 1;
+    return new CondyMissing();
+  }
+
+  public static Object throwing(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    calls = calls + 1;
+    throw new CondyError("no value");
+  }
+
+  public static Object throwingError(MethodHandles.Lookup lookup, String name, Class<?> type) {
+    calls = calls + 1;
+    throw new Error("fatal");
+  }
+
+  public static int calls() {
+    return calls;
+  }
+
+  public static String causeMessage(Throwable t) {
+    return t.getCause().getMessage();
+  }
+}
+
+// Named like an error but an exception, which bootstrap method errors wrap
+class CondyError extends RuntimeException {
+
+  CondyError(String message) {
+    super(message);
+  }
+}
@@ -0,0 +1,64 @@
+public class Constants {
+
+  public static int negativeByte() {
+    return -100;
+  }
+
+  public static int short1000() {
+    return 1000;
+  }
+
+  public static int negativeShort() {
+    return -30000;
+  }
+
+  public static long bigLong() {
+    return 1234567890123L;
+  }
+
+  public static double smallDouble() {
+    return 1.5e-10;
+  }
+
+  public static long mixed(long a) {
+    return a * 1000000007L + 200;
+  }
+
+  public static String greeting() {
+    return "hello";
+  }
+
+  public static String unicode() {
+    return "za\u017c\u00f3\u0142\u0107 \u263a";
+  }
+
+  // Modified UTF-8 encodes both in a different way than UTF-8
+  public static String nulAndEmoji() {
+    return "a\u0000b\uD83D\uDE00";
+  }
+
+  // String literals with the same text are the same object, also across classes
+  public static int sameLiteral() {
+    return greeting() == ConstantsOther.greeting() ? 1 : 0;
+  }
+
+  public static Object stringClass() {
+    return String.class;
+  }
+
+  public static String className() {
+    return Constants.class.getName();
+  }
+
+  public static String arrayClassName() {
+    return String[][].class.getName();
+  }
+
+  public static String primitiveArrayClassName() {
+    return int[].class.getName();
+  }
+
+  public static Object missingClass() {
+    return ConstantsMissing.class;
+  }
+}
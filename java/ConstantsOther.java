@@ -0,0 +1,10 @@
+public class ConstantsOther {
+
+  public static String greeting() {
+    return "hello";
+  }
+
+  public static Object stringClass() {
+    return String.class;
+  }
+}
@@ -0,0 +1,67 @@
+public class Conversions {
+
+  public static long i2l(int i) {
+    return i;
+  }
+
+  public static float i2f(int i) {
+    return i;
+  }
+
+  public static double i2d(int i) {
+    return i;
+  }
+
+  public static int l2i(long l) {
+    return (int) l;
+  }
+
+  public static float l2f(long l) {
+    return l;
+  }
+
+  public static double l2d(long l) {
+    return l;
+  }
+
+  public static int f2i(float f) {
+    return (int) f;
+  }
+
+  public static long f2l(float f) {
+    return (long) f;
+  }
+
+  public static double f2d(float f) {
+    return f;
+  }
+
+  public static int d2i(double d) {
+    return (int) d;
+  }
+
+  public static long d2l(double d) {
+    return (long) d;
+  }
+
+  public static float d2f(double d) {
+    return (float) d;
+  }
+
+  public static byte i2b(int i) {
+    return (byte) i;
+  }
+
+  public static char i2c(int i) {
+    return (char) i;
+  }
+
+  public static short i2s(int i) {
+    return (short) i;
+  }
+
+  // Mixes all numeric types in one expression
+  public static double mixed(int i, long l, float f, double d) {
+    return i * l + f / d;
+  }
+}
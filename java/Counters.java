@@ -0,0 +1,34 @@
+public class Counters {
+
+  private int count;
+  private long total;
+  private static long ticks;
+
+  public static int fieldIncrement() {
+    Counters c = new Counters();
+    c.count++;
+    int before = c.count++;
+    return before * 10 + c.count;
+  }
+
+  public static int longFieldIncrement() {
+    Counters c = new Counters();
+    c.total = 1;
+    long before = c.total++;
+    return (int) before * 10 + (int) c.total;
+  }
+
+  public static int staticIncrement() {
+    ticks = 1;
+    long before = ticks++;
+    return (int) before * 10 + (int) ticks;
+  }
+
+  public static int arrayIncrement(int x) {
+    int[] a = new int[2];
+    a[1] = x;
+    a[1] += x;
+    int before = a[1]++;
+    return before * 10 + a[1];
+  }
+}
@@ -0,0 +1,8 @@
+// Compiled against java/old/DefaultRight.java, so it neither resolves the side() conflict nor implements extra()
+class DefaultBoth implements DefaultLeft, DefaultRight {
+
+  static DefaultBoth create() {
+    return new DefaultBoth();
+  }
+
+}
@@ -0,0 +1,13 @@
+public class DefaultConflicts {
+
+  public static int callConflicting() {
+    DefaultLeft left = DefaultBoth.create();
+    return left.side();
+  }
+
+  public static int callMissing() {
+    DefaultRight right = DefaultBoth.create();
+    return right.extra();
+  }
+
+}
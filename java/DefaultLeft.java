@@ -0,0 +1,7 @@
+interface DefaultLeft {
+
+  default int side() {
+    return 1;
+  }
+
+}
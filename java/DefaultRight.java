@@ -0,0 +1,10 @@
+// See java/old/DefaultRight.java for how DefaultBoth is compiled
+interface DefaultRight {
+
+  default int side() {
+    return 2;
+  }
+
+  int extra();
+
+}
@@ -0,0 +1,134 @@
+public class Exceptions {
+
+  static int finallyCount;
+
+  public static String caught() {
+    try {
+      throw new IllegalStateException("boom");
+    } catch (IllegalStateException e) {
+      return e.getMessage();
+    }
+  }
+
+  // A handler catches subclasses of its catch type, the first matching handler wins
+  public static int subclass(int kind) {
+    try {
+      thrower(kind);
+      return 0;
+    } catch (IllegalArgumentException e) {
+      return 1;
+    } catch (RuntimeException e) {
+      return 2;
+    } catch (Throwable t) {
+      return 3;
+    }
+  }
+
+  static int thrower(int kind) {
+    switch (kind) {
+      case 1: throw new IllegalArgumentException();
+      case 2: throw new IllegalStateException();
+      case 3: throw new Error();
+      default: return kind;
+    }
+  }
+
+  // The operand stack is cleared before the handler runs
+  public static int operandStack() {
+    int result = 100;
+    try {
+      result += 10 + thrower(2);
+    } catch (RuntimeException e) {
+      result += 1;
+    }
+    return result;
+  }
+
+  // finally runs on normal completion as well as on an exception
+  public static int finallyBlocks(int kind) {
+    finallyCount = 0;
+    try {
+      try {
+        thrower(kind);
+      } finally {
+        finallyCount += 1;
+      }
+    } catch (RuntimeException e) {
+      finallyCount += 10;
+    }
+    return finallyCount;
+  }
+
+  // The exception unwinds through every recursive frame
+  public static String unwinding(int depth) {
+    try {
+      recurse(depth);
+      return null;
+    } catch (IllegalStateException e) {
+      return e.getMessage();
+    }
+  }
+
+  static void recurse(int depth) {
+    if (depth == 0) {
+      throw new IllegalStateException("bottom");
+    }
+    recurse(depth - 1);
+  }
+
+  public static String rethrown() {
+    try {
+      try {
+        throw new IllegalArgumentException("inner");
+      } catch (IllegalArgumentException e) {
+        throw new IllegalStateException("outer", e);
+      }
+    } catch (IllegalStateException e) {
+      return e.getCause().getMessage();
+    }
+  }
+
+  // Resolving the catch type of the inner handler fails, the NoClassDefFoundError replaces the thrown exception
+  public static String missingCatchType() {
+    try {
+      catchMissing();
+      return null;
+    } catch (Throwable t) {
+      return t instanceof NoClassDefFoundError ? t.getMessage() : null;
+    }
+  }
+
+  static int catchMissing() {
+    try {
+      throw new IllegalStateException("thrown");
+    } catch (ExceptionsMissing e) {
+      return -1;
+    }
+  }
+
+  public static int uncaught(int value) {
+    if (value > 0) {
+      throw new ExceptionsFailure("value " + value);
+    }
+    return value;
+  }
+
+  public static void withoutMessage() {
+    throw new Error();
+  }
+
+  public static String messageOf(Throwable t) {
+    return t.getMessage();
+  }
+
+  public static void throwNull() {
+    RuntimeException e = null;
+    throw e;
+  }
+}
+
+class ExceptionsFailure extends RuntimeException {
+  ExceptionsFailure(String message) {
+    super(message);
+  }
+}
@@ -0,0 +1,78 @@
+public class Fields {
+
+  int x;
+  long big;
+  double ratio;
+  float scale;
+  boolean flag;
+  int[] data;
+
+  public static int sum() {
+    Point3 p = Point3.create(1, 2, 3);
+    // the Fieldrefs below name Point3, the one in Point.readX names Point, both must hit the same slot
+    return p.x + p.y + p.z + Point.readX(p);
+  }
+
+  public static int shadowed() {
+    Point3 p = Point3.create(1, 2, 3);
+    return p.shadow() + ((Point) p).shadow;
+  }
+
+  public static long defaultLong() {
+    Fields f = new Fields();
+    return f.big;
+  }
+
+  public static double defaultDouble() {
+    Fields f = new Fields();
+    return f.ratio;
+  }
+
+  public static float defaultFloat() {
+    Fields f = new Fields();
+    return f.scale;
+  }
+
+  public static int defaultInts() {
+    Fields f = new Fields();
+    f.x = f.x + 7;
+    return f.x;
+  }
+
+  public static boolean defaultBoolean() {
+    Fields f = new Fields();
+    return f.flag;
+  }
+
+}
+
+class Point {
+
+  int x;
+  int y;
+  int shadow = 10;
+
+  static int readX(Point p) {
+    return p.x;
+  }
+
+}
+
+class Point3 extends Point {
+
+  int z;
+  int shadow = 20;
+
+  static Point3 create(int x, int y, int z) {
+    Point3 p = new Point3();
+    p.x = x;
+    p.y = y;
+    p.z = z;
+    return p;
+  }
+
+  int shadow() {
+    return shadow;
+  }
+
+}
@@ -0,0 +1,12 @@
+// See java/old/FinalBase.java for how FinalOverride is compiled
+public class FinalBase {
+
+  final int value() {
+    return 1;
+  }
+
+  public static int call() {
+    return FinalOverride.create().value();
+  }
+
+}
@@ -0,0 +1,12 @@
+// Compiled against java/old/FinalBase.java, where value() could still be overridden
+public class FinalOverride extends FinalBase {
+
+  static FinalBase create() {
+    return new FinalOverride();
+  }
+
+  int value() {
+    return 2;
+  }
+
+}
@@ -0,0 +1,46 @@
+public class FloatMath {
+
+  public static float fadd(float a, float b) {
+    return a + b;
+  }
+
+  public static float fsub(float a, float b) {
+    return a - b;
+  }
+
+  public static float fmul(float a, float b) {
+    return a * b;
+  }
+
+  public static float fdiv(float a, float b) {
+    return a / b;
+  }
+
+  public static float frem(float a, float b) {
+    return a % b;
+  }
+
+  public static double dadd(double a, double b) {
+    return a + b;
+  }
+
+  public static double dsub(double a, double b) {
+    return a - b;
+  }
+
+  public static double dmul(double a, double b) {
+    return a * b;
+  }
+
+  public static double ddiv(double a, double b) {
+    return a / b;
+  }
+
+  public static double drem(double a, double b) {
+    return a % b;
+  }
+
+  public static double dneg(double a) {
+    return -a;
+  }
+}
@@ -0,0 +1,47 @@
+// Handles.class is generated by gen/GenHandles.java, compile with: javac -cp java -d java java/HandleTests.java
+public class HandleTests {
+
+  public static int fields() {
+    HandlesTarget target = new HandlesTarget(5);
+    Handles.putField(target, 9);
+    return Handles.getField(target) + target.value;
+  }
+
+  public static int statics() {
+    Handles.putStatic(7);
+    return Handles.getStatic() + HandlesTarget.counter;
+  }
+
+  public static int invokeVirtual() {
+    return Handles.invokeVirtual(new HandlesSub(0));
+  }
+
+  public static int invokeSpecial() {
+    return Handles.invokeSpecial(new HandlesSub(0));
+  }
+
+  public static int invokeStatic() {
+    return Handles.invokeStatic(40, 2);
+  }
+
+  public static int newInvokeSpecial() {
+    return Handles.newInvokeSpecial(33).value;
+  }
+
+  public static int invokeInterface() {
+    return Handles.invokeInterface(new HandlesSub(4));
+  }
+
+  public static long wrongType() {
+    return Handles.wrongType(1, 2);
+  }
+
+  public static int invokeAdapted() {
+    Integer result = (Integer) Handles.invokeAdapted(Integer.valueOf(40), 2);
+    return result.intValue();
+  }
+
+  public static Object invokeWrongArity() {
+    return Handles.invokeWrongArity(1, 2, 3);
+  }
+}
@@ -0,0 +1,35 @@
+public class HandlesTarget implements HandlesShape {
+  static int counter;
+
+  int value;
+
+  HandlesTarget(int value) {
+    this.value = value;
+  }
+
+  static int add(int a, int b) {
+    return a + b;
+  }
+
+  int describe() {
+    return 1;
+  }
+
+  public int sides() {
+    return value;
+  }
+}
+
+class HandlesSub extends HandlesTarget {
+  HandlesSub(int value) {
+    super(value);
+  }
+
+  int describe() {
+    return 2;
+  }
+}
+
+interface HandlesShape {
+  int sides();
+}
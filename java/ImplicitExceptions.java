@@ -0,0 +1,148 @@
+import java.util.function.Function;
+
+public class ImplicitExceptions {
+
+  static int depth;
+
+  static Function<Integer, Integer> countdown;
+
+  public static String divide(int a, int b) {
+    try {
+      return a / b == 0 ? "zero" : "non-zero";
+    } catch (ArithmeticException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String readField() {
+    ImplicitPoint p = null;
+    try {
+      return p.x == 0 ? "zero" : "non-zero";
+    } catch (NullPointerException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String assignField() {
+    ImplicitPoint p = null;
+    try {
+      p.x = 1;
+      return null;
+    } catch (RuntimeException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String invokeNull() {
+    ImplicitPoint p = null;
+    try {
+      return p.name();
+    } catch (NullPointerException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String cast() {
+    Object o = Integer.valueOf(1);
+    try {
+      return (String) o;
+    } catch (ClassCastException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String arrayStore() {
+    Object[] a = new String[1];
+    try {
+      a[0] = Integer.valueOf(1);
+      return null;
+    } catch (ArrayStoreException e) {
+      return e.getMessage();
+    }
+  }
+
+  // A handler for a super class catches the more specific exception
+  public static String arrayIndex(int index) {
+    int[] a = new int[3];
+    try {
+      return a[index] == 0 ? "zero" : "non-zero";
+    } catch (IndexOutOfBoundsException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String arraySize(int size) {
+    try {
+      return new Object[size].length == 0 ? "empty" : "non-empty";
+    } catch (NegativeArraySizeException e) {
+      return e.getMessage();
+    }
+  }
+
+  // The exception raised deep down in the callee is caught here
+  public static int stackOverflow() {
+    depth = 0;
+    try {
+      recurse();
+      return -1;
+    } catch (StackOverflowError e) {
+      return e.getMessage() == null ? depth : -2;
+    }
+  }
+
+  static void recurse() {
+    depth++;
+    recurse();
+  }
+
+  // Calls through a lambda are made by the VM itself, their recursion is cut short the same way
+  public static int lambdaStackOverflow() {
+    depth = 0;
+    countdown = n -> {
+      depth++;
+      return countdown.apply(n + 1);
+    };
+    try {
+      countdown.apply(0);
+      return -1;
+    } catch (StackOverflowError e) {
+      return depth;
+    }
+  }
+
+  // javac exits the monitor in a catch-all handler before rethrowing
+  public static int synchronizedBlock(Object lock, int divisor) {
+    try {
+      synchronized (lock) {
+        return 10 / divisor;
+      }
+    } catch (ArithmeticException e) {
+      synchronized (lock) {
+        return -1;
+      }
+    }
+  }
+
+  public static String synchronizedNull() {
+    Object lock = null;
+    try {
+      synchronized (lock) {
+        return null;
+      }
+    } catch (NullPointerException e) {
+      return e.getMessage();
+    }
+  }
+
+  public static String uncaught(int[] a) {
+    return a[0] == 0 ? "zero" : "non-zero";
+  }
+}
+
+class ImplicitPoint {
+  int x;
+
+  String name() {
+    return "point";
+  }
+}
@@ -0,0 +1,61 @@
+public class IntMath {
+
+  public static int add(int a, int b) {
+    return a + b;
+  }
+
+  public static int sub(int a, int b) {
+    return a - b;
+  }
+
+  public static int mul(int a, int b) {
+    return a * b;
+  }
+
+  public static int div(int a, int b) {
+    return a / b;
+  }
+
+  public static int rem(int a, int b) {
+    return a % b;
+  }
+
+  public static int neg(int a) {
+    return -a;
+  }
+
+  public static int shl(int a, int distance) {
+    return a << distance;
+  }
+
+  public static int shr(int a, int distance) {
+    return a >> distance;
+  }
+
+  public static int ushr(int a, int distance) {
+    return a >>> distance;
+  }
+
+  public static int and(int a, int b) {
+    return a & b;
+  }
+
+  public static int or(int a, int b) {
+    return a | b;
+  }
+
+  public static int xor(int a, int b) {
+    return a ^ b;
+  }
+
+  public static int inc(int a) {
+    a += 100;
+    a++;
+    return a;
+  }
+
+  public static int dec(int a) {
+    a -= 128;
+    return a;
+  }
+}
@@ -0,0 +1,117 @@
+public class Interfaces {
+
+  public static int dispatch() {
+    Shape s = Square.create(3);
+    return s.area();
+  }
+
+  public static int defaultMethod() {
+    Shape s = Square.create(3);
+    return s.describe();
+  }
+
+  public static int defaultMethodThroughClass() {
+    // invokevirtual resolving to a default method
+    Square s = Square.create(3);
+    return s.describe();
+  }
+
+  public static int inheritedImplementation() {
+    // area() is implemented by the super class, which does not implement Shape itself
+    Shape s = Rectangle.create();
+    return s.area();
+  }
+
+  public static int maximallySpecific() {
+    Named n = Tagged.create();
+    return n.name();
+  }
+
+  public static int staticMethod() {
+    return Shape.unit();
+  }
+
+  public static int privateMethod() {
+    Shape s = Square.create(3);
+    return s.doubled();
+  }
+
+}
+
+interface Shape {
+
+  int area();
+
+  default int describe() {
+    return area() + 100;
+  }
+
+  default int doubled() {
+    return twice(area());
+  }
+
+  private int twice(int value) {
+    return value + value;
+  }
+
+  static int unit() {
+    return 1;
+  }
+
+}
+
+class Square implements Shape {
+
+  int side;
+
+  static Square create(int side) {
+    Square s = new Square();
+    s.side = side;
+    return s;
+  }
+
+  public int area() {
+    return side + side + side;
+  }
+
+}
+
+class Quad {
+
+  public int area() {
+    return 8;
+  }
+
+}
+
+class Rectangle extends Quad implements Shape {
+
+  static Shape create() {
+    return new Rectangle();
+  }
+
+}
+
+interface Named {
+
+  default int name() {
+    return 1;
+  }
+
+}
+
+interface Labelled extends Named {
+
+  default int name() {
+    return 2;
+  }
+
+}
+
+class Tagged implements Named, Labelled {
+
+  static Named create() {
+    return new Tagged();
+  }
+
+}
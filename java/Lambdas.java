@@ -0,0 +1,80 @@
+import java.io.Serializable;
+import java.util.Comparator;
+import java.util.function.Function;
+
+public class Lambdas {
+
+  private static int counter;
+
+  public static int runnable() {
+    Runnable r = () -> counter = counter + 1;
+    r.run();
+    r.run();
+    return counter;
+  }
+
+  public static int capturing(int a, int b) {
+    Function<Integer, Integer> add = x -> x + a;
+    return add.apply(b);
+  }
+
+  public static int comparator() {
+    Comparator<LambdaItem> byWeight = Lambdas::compareWeights;
+    return byWeight.compare(new LambdaItem(7), new LambdaItem(3));
+  }
+
+  // Unbound receiver, the int result is boxed
+  public static int virtualReference() {
+    Function<LambdaItem, Integer> weight = LambdaItem::weight;
+    return weight.apply(new LambdaItem(9));
+  }
+
+  // Bound receiver, captured when the lambda is created
+  public static int boundReference() {
+    LambdaItem item = new LambdaItem(20);
+    Function<Integer, Integer> plus = item::plus;
+    return plus.apply(5);
+  }
+
+  // The Integer argument is unboxed for the constructor
+  public static int constructorReference() {
+    Function<Integer, LambdaItem> create = LambdaItem::new;
+    return create.apply(12).weight();
+  }
+
+  // Intersection casts are bootstrapped by altMetafactory with FLAG_MARKERS
+  public static int markerInterface() {
+    Runnable r = (Runnable & LambdaMarker) () -> counter = 40;
+    r.run();
+    return counter;
+  }
+
+  // Serializable lambdas are bootstrapped by altMetafactory with FLAG_SERIALIZABLE
+  public static int serializable(int a) {
+    Function<Integer, Integer> negate = (Function<Integer, Integer> & Serializable) x -> -x + a;
+    return negate.apply(1);
+  }
+
+  private static int compareWeights(LambdaItem a, LambdaItem b) {
+    return a.weight() + -b.weight();
+  }
+}
+
+interface LambdaMarker {
+}
+
+class LambdaItem {
+  private final int weight;
+
+  LambdaItem(int weight) {
+    this.weight = weight;
+  }
+
+  int weight() {
+    return weight;
+  }
+
+  int plus(int other) {
+    return weight + other;
+  }
+}
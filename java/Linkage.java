@@ -0,0 +1,8 @@
+public class Linkage {
+
+  public static int callAbstract() {
+    LinkageBase b = LinkageImpl.create();
+    return b.value();
+  }
+
+}
@@ -0,0 +1,6 @@
+// See java/old/LinkageBase.java for how the Linkage classes are compiled
+abstract class LinkageBase {
+
+  abstract int value();
+
+}
@@ -0,0 +1,8 @@
+// Compiled against java/old/LinkageBase.java, so it does not implement value()
+class LinkageImpl extends LinkageBase {
+
+  static LinkageBase create() {
+    return new LinkageImpl();
+  }
+
+}
@@ -0,0 +1,8 @@
+// Compiled against java/old/LinkageBase.java, which still declares removed()
+public class LinkageRemoved {
+
+  public static int callRemoved() {
+    return LinkageBase.removed();
+  }
+
+}
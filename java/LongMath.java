@@ -0,0 +1,55 @@
+public class LongMath {
+
+  public static long add(long a, long b) {
+    return a + b;
+  }
+
+  public static long sub(long a, long b) {
+    return a - b;
+  }
+
+  public static long mul(long a, long b) {
+    return a * b;
+  }
+
+  public static long div(long a, long b) {
+    return a / b;
+  }
+
+  public static long rem(long a, long b) {
+    return a % b;
+  }
+
+  public static long neg(long a) {
+    return -a;
+  }
+
+  public static long shl(long a, int distance) {
+    return a << distance;
+  }
+
+  public static long shr(long a, int distance) {
+    return a >> distance;
+  }
+
+  public static long ushr(long a, int distance) {
+    return a >>> distance;
+  }
+
+  public static long and(long a, long b) {
+    return a & b;
+  }
+
+  public static long or(long a, long b) {
+    return a | b;
+  }
+
+  public static long xor(long a, long b) {
+    return a ^ b;
+  }
+
+  // The int shift distance sits on top of the long on the operand stack
+  public static long shiftAndAdd(long a, int distance, long b) {
+    return (a << distance) + b;
+  }
+}
@@ -0,0 +1,70 @@
+public class Methods extends MethodsBase implements Greeter, LoudGreeter {
+
+  static int pick(int i) {
+    return 1;
+  }
+
+  static int pick(long l) {
+    return 2;
+  }
+
+  static int pick(double d) {
+    return 4;
+  }
+
+  static int pick(Object o) {
+    return 8;
+  }
+
+  static Methods create() {
+    return new Methods();
+  }
+
+  // each overload returns a different power of two
+  public static int overloads() {
+    return pick(1) + pick(1L) + pick(1.0) + pick((Object) null);
+  }
+
+  public static int inheritedStatic() {
+    return Methods.baseStatic();
+  }
+
+  public static int inheritedInstance() {
+    Methods m = create();
+    return m.baseValue();
+  }
+
+  public static int defaultMethod() {
+    Methods m = create();
+    return m.greet();
+  }
+
+}
+
+class MethodsBase {
+
+  static int baseStatic() {
+    return 5;
+  }
+
+  int baseValue() {
+    return 6;
+  }
+
+}
+
+interface Greeter {
+
+  default int greet() {
+    return 1;
+  }
+
+}
+
+interface LoudGreeter extends Greeter {
+
+  default int greet() {
+    return 2;
+  }
+
+}
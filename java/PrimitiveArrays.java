@@ -0,0 +1,84 @@
+public class PrimitiveArrays {
+
+  // Every element type starts out with its default value
+  public static int defaults() {
+    boolean[] z = new boolean[1];
+    byte[] b = new byte[1];
+    char[] c = new char[1];
+    short[] s = new short[1];
+    int[] i = new int[1];
+    long[] l = new long[1];
+    float[] f = new float[1];
+    double[] d = new double[1];
+    return (z[0] ? 1 : 0) + b[0] + c[0] + s[0] + i[0] + (int) l[0] + (int) f[0] + (int) d[0];
+  }
+
+  public static int length(int n) {
+    return new long[n].length;
+  }
+
+  public static int bytes(int value) {
+    byte[] b = new byte[2];
+    b[1] = (byte) value;
+    return b[1];
+  }
+
+  public static int chars(int value) {
+    char[] c = new char[2];
+    c[1] = (char) value;
+    return c[1];
+  }
+
+  public static int shorts(int value) {
+    short[] s = new short[2];
+    s[1] = (short) value;
+    return s[1];
+  }
+
+  public static boolean booleans(boolean value) {
+    boolean[] z = new boolean[2];
+    z[1] = value;
+    return z[1];
+  }
+
+  public static long longs(long value) {
+    long[] l = new long[3];
+    l[2] = value;
+    l[1] = l[2] * 2;
+    return l[0] + l[1] + l[2];
+  }
+
+  public static float floats(float value) {
+    float[] f = new float[2];
+    f[0] = value;
+    f[1] += f[0] / 2;
+    return f[1];
+  }
+
+  public static double doubles(double value) {
+    double[] d = new double[2];
+    d[1] = value;
+    d[0] = -d[1];
+    return d[0];
+  }
+
+  public static int sum(int n) {
+    int[] values = new int[n];
+    for (int i = 0; i < values.length; i++) {
+      values[i] = i * i;
+    }
+
+    int sum = 0;
+    for (int v : values) {
+      sum += v;
+    }
+    return sum;
+  }
+
+  public static String fromChars() {
+    char[] c = new char[2];
+    c[0] = 'h';
+    c[1] = 'i';
+    return c.length + "" + c[0] + c[1];
+  }
+}
@@ -0,0 +1,90 @@
+public class ReferenceArrays {
+
+  public static int count(String[] args) {
+    return args.length;
+  }
+
+  public static String last(String[] args) {
+    return args[args.length - 1];
+  }
+
+  // Elements start out null
+  public static int defaults(int n) {
+    Object[] objects = new Object[n];
+    int nulls = 0;
+    for (Object o : objects) {
+      if (o == null) {
+        nulls++;
+      }
+    }
+    return nulls;
+  }
+
+  public static String store(String text) {
+    String[] strings = new String[2];
+    strings[1] = text;
+    return strings[1];
+  }
+
+  public static int grid(int rows, int columns) {
+    int[][] grid = new int[rows][columns];
+    for (int i = 0; i < rows; i++) {
+      for (int j = 0; j < columns; j++) {
+        grid[i][j] = i * columns + j;
+      }
+    }
+    return grid[rows - 1][columns - 1] + grid.length * 100 + grid[0].length * 1000;
+  }
+
+  // Only the first dimension is allocated
+  public static int partial() {
+    String[][] strings = new String[3][];
+    strings[1] = new String[] {"a", "b"};
+    return (strings[0] == null ? 1 : 0) + strings[1].length * 10;
+  }
+
+  public static String jagged() {
+    String[][][] cube = new String[2][3][4];
+    cube[1][2][3] = "deep";
+    return cube[1][2][3];
+  }
+
+  // Arrays are covariant, a String[] can be stored in an Object[][] slot
+  public static int covariant() {
+    Object[][] objects = new Object[1][];
+    objects[0] = new String[] {"x"};
+    return objects[0].length;
+  }
+
+  public static void storeInteger() {
+    Object[] objects = new String[1];
+    objects[0] = Integer.valueOf(1);
+  }
+
+  public static void storeIntArray() {
+    Object[] objects = new String[1][];
+    objects[0] = new int[1];
+  }
+
+  public static int instances() {
+    Object strings = new String[0];
+    Object ints = new int[0][0];
+    int result = 0;
+    result += strings instanceof Object[] ? 1 : 0;
+    result += strings instanceof String[] ? 10 : 0;
+    result += strings instanceof Integer[] ? 100 : 0;
+    result += ints instanceof Object[] ? 1000 : 0;
+    result += ints instanceof int[][] ? 10000 : 0;
+    result += ints instanceof long[][] ? 100000 : 0;
+    result += ints instanceof java.io.Serializable ? 1000000 : 0;
+    return result;
+  }
+
+  public static int objects(Object o) {
+    return (o instanceof String ? 1 : 0) + (o instanceof Integer ? 10 : 0) + (o instanceof Object ? 100 : 0);
+  }
+
+  public static String arrayClassName() {
+    return String[][].class.getName();
+  }
+}
@@ -0,0 +1,99 @@
+public class Special {
+
+  public static int constructorChain() {
+    SpecialChild c = new SpecialChild(5);
+    return c.parentValue + c.childValue;
+  }
+
+  public static int superCalls() {
+    // every override calls super.value(), which must not dispatch back to the receiver's class
+    SpecialParent p = new SpecialGrandChild(0);
+    return p.value();
+  }
+
+  public static int superCallToInheritedMethod() {
+    SpecialGrandChild c = new SpecialGrandChild(0);
+    return c.callBase();
+  }
+
+  public static int interfaceSuperCall() {
+    SpecialGrandChild c = new SpecialGrandChild(0);
+    return c.greeting();
+  }
+
+  public static int newAllocatesReferencedClass() {
+    SpecialParent p = new SpecialChild(1);
+    return p.value();
+  }
+
+}
+
+class SpecialBase {
+
+  int base() {
+    return 99;
+  }
+
+}
+
+class SpecialParent extends SpecialBase {
+
+  int parentValue;
+
+  SpecialParent(int value) {
+    super();
+    this.parentValue = value;
+  }
+
+  int value() {
+    return 1;
+  }
+
+}
+
+class SpecialChild extends SpecialParent {
+
+  int childValue;
+
+  SpecialChild(int value) {
+    super(value + value);
+    this.childValue = value;
+  }
+
+  int value() {
+    return super.value() + 10;
+  }
+
+}
+
+interface SpecialGreeter {
+
+  default int greeting() {
+    return 7;
+  }
+
+}
+
+class SpecialGrandChild extends SpecialChild implements SpecialGreeter {
+
+  SpecialGrandChild(int value) {
+    super(value);
+  }
+
+  int value() {
+    return super.value() + 100;
+  }
+
+  int base() {
+    return 1;
+  }
+
+  int callBase() {
+    return super.base();
+  }
+
+  public int greeting() {
+    return SpecialGreeter.super.greeting() + 1;
+  }
+
+}
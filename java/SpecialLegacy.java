@@ -0,0 +1,27 @@
+// Compiled with --release 8, which calls private methods through invokespecial:
+//
+//   javac --release 8 -d java java/SpecialLegacy.java
+public class SpecialLegacy {
+
+  private int secret() {
+    return 42;
+  }
+
+  public static int callPrivate() {
+    SpecialLegacyChild c = new SpecialLegacyChild();
+    return c.reveal();
+  }
+
+  int reveal() {
+    return secret();
+  }
+
+}
+
+class SpecialLegacyChild extends SpecialLegacy {
+
+  int secret() {
+    return 0;
+  }
+
+}
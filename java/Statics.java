@@ -0,0 +1,45 @@
+public class Statics extends StaticsBase implements StaticsConstants {
+
+  static final int ANSWER = 42;
+  static final long BIG = 1L << 40;
+
+  static int counter;
+  static long total = 1L;
+  static double ratio;
+
+  static {
+    counter = 10;
+  }
+
+  public static int increment() {
+    counter++;
+    return counter;
+  }
+
+  public static long addToTotal(long l) {
+    total += l;
+    return total;
+  }
+
+  public static int inherited() {
+    // both resolve through the super class and the super interface of Statics
+    return Statics.baseCounter + Statics.LIMITS[1];
+  }
+
+  public static double ratio() {
+    return ratio;
+  }
+
+}
+
+class StaticsBase {
+
+  static int baseCounter = 5;
+
+}
+
+interface StaticsConstants {
+
+  int[] LIMITS = {1, 2, 3};
+
+}
@@ -0,0 +1,80 @@
+public class Virtual {
+
+  public static int overridden() {
+    Animal a = Dog.create();
+    return a.sound();
+  }
+
+  public static int inheritedOverride() {
+    // Puppy does not override sound(), the one from Dog is selected
+    Animal a = Puppy.create();
+    return a.sound() + a.legs();
+  }
+
+  public static int abstractMethod() {
+    Animal a = Dog.create();
+    return a.describe();
+  }
+
+  public static int finalMethod() {
+    Animal a = Dog.create();
+    return a.id();
+  }
+
+  public static int nullReceiver() {
+    Animal a = null;
+    return a.sound();
+  }
+
+}
+
+abstract class Animal {
+
+  int sound() {
+    return 1;
+  }
+
+  int legs() {
+    return 4;
+  }
+
+  final int id() {
+    return 7;
+  }
+
+  abstract int kind();
+
+  // calls through this are dispatched to the receiver's class too
+  int describe() {
+    return kind() + sound();
+  }
+
+}
+
+class Dog extends Animal {
+
+  static Animal create() {
+    return new Dog();
+  }
+
+  int sound() {
+    return 2;
+  }
+
+  int kind() {
+    return 10;
+  }
+
+}
+
+class Puppy extends Dog {
+
+  static Animal create() {
+    return new Puppy();
+  }
+
+  int legs() {
+    return 3;
+  }
+
+}
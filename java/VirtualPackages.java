@@ -0,0 +1,19 @@
+import vpkg.a.A;
+import vpkg.a.C;
+import vpkg.b.B;
+
+public class VirtualPackages {
+
+  public static int callAOnB() {
+    return A.callM(B.create());
+  }
+
+  public static int callAOnC() {
+    return A.callM(C.create());
+  }
+
+  public static int callBOnC() {
+    return B.callM(C.create());
+  }
+
+}
@@ -0,0 +1,54 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/ArrayStores.class, which stores ints to small arrays without narrowing them first. javac always
+ * narrows with i2b, i2c or i2s, leaving the truncation done by the store instructions themselves untested.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenArrayStores.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenArrayStores java/ArrayStores.class
+ */
+public class GenArrayStores {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenArrayStores gen = new GenArrayStores();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "ArrayStores", null, "java/lang/Object", null);
+
+    // Stores the int argument to a new array and loads it back
+    storeAndLoad("booleans", T_BOOLEAN, BASTORE, BALOAD);
+    storeAndLoad("bytes", T_BYTE, BASTORE, BALOAD);
+    storeAndLoad("chars", T_CHAR, CASTORE, CALOAD);
+    storeAndLoad("shorts", T_SHORT, SASTORE, SALOAD);
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  private void storeAndLoad(String name, int type, int store, int load) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, "(I)I", null, null);
+    mv.visitCode();
+    mv.visitInsn(ICONST_1);
+    mv.visitIntInsn(NEWARRAY, type);
+    mv.visitVarInsn(ASTORE, 1);
+    mv.visitVarInsn(ALOAD, 1);
+    mv.visitInsn(ICONST_0);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(store);
+    mv.visitVarInsn(ALOAD, 1);
+    mv.visitInsn(ICONST_0);
+    mv.visitInsn(load);
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
@@ -0,0 +1,93 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Handle;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/BadOperands.class, whose methods use operands of the wrong type. The verifier would reject them,
+ * curlyvm does not verify classes and fails with an internal error instead.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenBadOperands.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenBadOperands java/BadOperands.class
+ */
+public class GenBadOperands {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenBadOperands gen = new GenBadOperands();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V1_6, ACC_PUBLIC | ACC_SUPER, "BadOperands", null, "java/lang/Object", null);
+
+    MethodVisitor mv = method("compareInts", "()I");
+    mv.visitInsn(ICONST_1);
+    mv.visitInsn(ICONST_2);
+    mv.visitInsn(FCMPL);
+    end(mv, IRETURN);
+
+    mv = method("convertNull", "()J");
+    mv.visitInsn(ACONST_NULL);
+    mv.visitInsn(I2L);
+    end(mv, LRETURN);
+
+    Label zero = new Label();
+    mv = method("branchOnNull", "()I");
+    mv.visitInsn(ACONST_NULL);
+    mv.visitJumpInsn(IFEQ, zero);
+    mv.visitInsn(ICONST_0);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(zero);
+    mv.visitInsn(ICONST_1);
+    end(mv, IRETURN);
+
+    mv = method("storeIntToObjects", "()V");
+    mv.visitInsn(ICONST_1);
+    mv.visitTypeInsn(ANEWARRAY, "java/lang/Object");
+    mv.visitInsn(ICONST_0);
+    mv.visitInsn(ICONST_5);
+    mv.visitInsn(IASTORE);
+    end(mv, RETURN);
+
+    mv = method("add", "(II)I");
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitVarInsn(ILOAD, 1);
+    mv.visitInsn(IADD);
+    end(mv, IRETURN);
+
+    // Invokes add with a single int on the stack
+    mv = method("missingArgument", "()I");
+    mv.visitInsn(ICONST_1);
+    mv.visitMethodInsn(INVOKESTATIC, "BadOperands", "add", "(II)I", false);
+    end(mv, IRETURN);
+
+    // The same for a call site, whose bootstrap method does not exist as it is never reached
+    Handle bootstrap = new Handle(H_INVOKESTATIC, "BadOperands", "bootstrap",
+        "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;", false);
+    mv = method("missingDynamicArgument", "()I");
+    mv.visitInsn(ICONST_1);
+    mv.visitInvokeDynamicInsn("add", "(II)I", bootstrap);
+    end(mv, IRETURN);
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  private MethodVisitor method(String name, String desc) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, desc, null, null);
+    mv.visitCode();
+    return mv;
+  }
+
+  private void end(MethodVisitor mv, int returnOpcode) {
+    mv.visitInsn(returnOpcode);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
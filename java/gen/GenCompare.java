@@ -0,0 +1,47 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Compare.class, javac only emits the comparison instructions followed by a branch.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenCompare.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenCompare java/Compare.class
+ */
+public class GenCompare {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenCompare gen = new GenCompare();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "Compare", null, "java/lang/Object", null);
+
+    compare("lcmp", "(JJ)I", LLOAD, 2, LCMP);
+    compare("fcmpl", "(FF)I", FLOAD, 1, FCMPL);
+    compare("fcmpg", "(FF)I", FLOAD, 1, FCMPG);
+    compare("dcmpl", "(DD)I", DLOAD, 2, DCMPL);
+    compare("dcmpg", "(DD)I", DLOAD, 2, DCMPG);
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  // Returns the result of comparing both arguments, which take the given number of local variable slots each
+  private void compare(String name, String desc, int load, int size, int opcode) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, desc, null, null);
+    mv.visitCode();
+    mv.visitVarInsn(load, 0);
+    mv.visitVarInsn(load, size);
+    mv.visitInsn(opcode);
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
@@ -0,0 +1,75 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.ConstantDynamic;
+import jdk.internal.org.objectweb.asm.Handle;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+import jdk.internal.org.objectweb.asm.Type;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Condy.class, javac never emits ldc of CONSTANT_Dynamic entries.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenCondy.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenCondy java/Condy.class
+ */
+public class GenCondy {
+
+  private static final String BOOTSTRAPS = "CondyBootstraps";
+  private static final String BOOTSTRAP_PREFIX = "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;";
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenCondy gen = new GenCondy();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "Condy", null, "java/lang/Object", null);
+
+    // The Integer returned by the bootstrap method is unboxed
+    load("answer", constant("answer", "I", "answer", ")Ljava/lang/Object;"));
+
+    // Both methods load the same constant, the bootstrap method only runs once
+    load("answerAgain", constant("answer", "I", "answer", ")Ljava/lang/Object;"));
+
+    load("sum", constant("sum", "I", "sum", "II)I", 40, 2));
+    load("name", constant("greeting", "Ljava/lang/String;", "name", ")Ljava/lang/Object;"));
+    load("lookupClass", constant("lookupClass", "Ljava/lang/String;", "lookupClass", ")Ljava/lang/Object;"));
+
+    // A constant used as a static argument of another one
+    ConstantDynamic first = constant("first", "I", "sum", "II)I", 30, 5);
+    load("nested", constant("nested", "I", "sum", "II)I", first, 7));
+
+    load("failing", constant("failing", "Ljava/lang/Object;", "failing", ")Ljava/lang/Object;"));
+    load("missingClass", constant("missingClass", "Ljava/lang/Object;", "missingClass", ")Ljava/lang/Object;"));
+
+    load("throwing", constant("throwing", "Ljava/lang/Object;", "throwing", ")Ljava/lang/Object;"));
+    load("throwingError", constant("throwingError", "Ljava/lang/Object;", "throwingError", ")Ljava/lang/Object;"));
+
+    // The bootstrap method returns a String for an int constant
+    load("wrongType", constant("wrongType", "I", "name", ")Ljava/lang/Object;"));
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  private static ConstantDynamic constant(String name, String desc, String bootstrap, String bootstrapDesc, Object... args) {
+    Handle handle = new Handle(H_INVOKESTATIC, BOOTSTRAPS, bootstrap, BOOTSTRAP_PREFIX + bootstrapDesc, false);
+    return new ConstantDynamic(name, desc, handle, args);
+  }
+
+  // Loads the constant and returns it
+  private void load(String name, ConstantDynamic constant) {
+    Type type = Type.getType(constant.getDescriptor());
+
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, Type.getMethodDescriptor(type), null, null);
+    mv.visitCode();
+    mv.visitLdcInsn(constant);
+    mv.visitInsn(type.getOpcode(IRETURN));
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
@@ -0,0 +1,107 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Handle;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+import jdk.internal.org.objectweb.asm.Type;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Handles.class, javac never emits ldc of MethodHandle and MethodType constants.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenHandles.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenHandles java/Handles.class
+ */
+public class GenHandles {
+
+  private static final String TARGET = "HandlesTarget";
+  private static final String MH = "java/lang/invoke/MethodHandle";
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenHandles gen = new GenHandles();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "Handles", null, "java/lang/Object", null);
+
+    invokeExact("getField", "(LHandlesTarget;)I", new Handle(H_GETFIELD, TARGET, "value", "I", false));
+    invokeExact("putField", "(LHandlesTarget;I)V", new Handle(H_PUTFIELD, TARGET, "value", "I", false));
+    invokeExact("getStatic", "()I", new Handle(H_GETSTATIC, TARGET, "counter", "I", false));
+    invokeExact("putStatic", "(I)V", new Handle(H_PUTSTATIC, TARGET, "counter", "I", false));
+    invokeExact("invokeVirtual", "(LHandlesTarget;)I", new Handle(H_INVOKEVIRTUAL, TARGET, "describe", "()I", false));
+    invokeExact("invokeStatic", "(II)I", new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+    invokeExact("invokeSpecial", "(LHandlesTarget;)I", new Handle(H_INVOKESPECIAL, TARGET, "describe", "()I", false));
+    invokeExact("newInvokeSpecial", "(I)LHandlesTarget;", new Handle(H_NEWINVOKESPECIAL, TARGET, "<init>", "(I)V", false));
+    invokeExact("invokeInterface", "(LHandlesShape;)I", new Handle(H_INVOKEINTERFACE, "HandlesShape", "sides", "()I", true));
+
+    // (int, int)int invoked exactly as (int, int)long
+    invokeExact("wrongType", "(II)J", new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+
+    // (int, int)int invoked as (Integer, int)Object
+    invoke("invokeAdapted", "(Ljava/lang/Integer;I)Ljava/lang/Object;", new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+
+    // (int, int)int invoked as (int, int, int)Object
+    invoke("invokeWrongArity", "(III)Ljava/lang/Object;", new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+
+    MethodVisitor mv = method("handle", "()Ljava/lang/invoke/MethodHandle;");
+    mv.visitLdcInsn(new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+    mv.visitInsn(ARETURN);
+    end(mv);
+
+    mv = method("handleType", "()Ljava/lang/String;");
+    mv.visitLdcInsn(new Handle(H_INVOKESTATIC, TARGET, "add", "(II)I", false));
+    mv.visitMethodInsn(INVOKEVIRTUAL, MH, "type", "()Ljava/lang/invoke/MethodType;", false);
+    mv.visitMethodInsn(INVOKEVIRTUAL, "java/lang/invoke/MethodType", "toMethodDescriptorString", "()Ljava/lang/String;", false);
+    mv.visitInsn(ARETURN);
+    end(mv);
+
+    mv = method("methodType", "()Ljava/lang/String;");
+    mv.visitLdcInsn(Type.getMethodType("(ILjava/lang/String;)V"));
+    mv.visitMethodInsn(INVOKEVIRTUAL, "java/lang/invoke/MethodType", "toMethodDescriptorString", "()Ljava/lang/String;", false);
+    mv.visitInsn(ARETURN);
+    end(mv);
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  // Loads the handle, passes all arguments of the method to it and returns the result
+  private void invokeExact(String name, String desc, Handle handle) {
+    callHandle(name, desc, handle, "invokeExact");
+  }
+
+  private void invoke(String name, String desc, Handle handle) {
+    callHandle(name, desc, handle, "invoke");
+  }
+
+  private void callHandle(String name, String desc, Handle handle, String invoker) {
+    MethodVisitor mv = method(name, desc);
+    mv.visitLdcInsn(handle);
+
+    Type type = Type.getMethodType(desc);
+    int local = 0;
+    for (Type arg : type.getArgumentTypes()) {
+      mv.visitVarInsn(arg.getOpcode(ILOAD), local);
+      local += arg.getSize();
+    }
+
+    mv.visitMethodInsn(INVOKEVIRTUAL, MH, invoker, desc, false);
+    mv.visitInsn(type.getReturnType().getOpcode(IRETURN));
+    end(mv);
+  }
+
+  private MethodVisitor method(String name, String desc) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, desc, null, null);
+    mv.visitCode();
+    return mv;
+  }
+
+  private static void end(MethodVisitor mv) {
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
@@ -0,0 +1,85 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Monitors.class, which exits monitors it never entered. javac pairs every monitorenter with a
+ * monitorexit, so IllegalMonitorStateException cannot be provoked from Java source.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenMonitors.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenMonitors java/Monitors.class
+ */
+public class GenMonitors {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_FRAMES);
+
+  public static void main(String[] args) throws Exception {
+    GenMonitors gen = new GenMonitors();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "Monitors", null, "java/lang/Object", null);
+
+    exitUnowned();
+    exitTwice();
+    catchUnowned();
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  // Exits the monitor of the argument
+  private void exitUnowned() {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "exitUnowned", "(Ljava/lang/Object;)V", null, null);
+    mv.visitCode();
+    mv.visitVarInsn(ALOAD, 0);
+    mv.visitInsn(MONITOREXIT);
+    mv.visitInsn(RETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+
+  // Enters the monitor of the argument once and exits it twice
+  private void exitTwice() {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "exitTwice", "(Ljava/lang/Object;)V", null, null);
+    mv.visitCode();
+    mv.visitVarInsn(ALOAD, 0);
+    mv.visitInsn(MONITORENTER);
+    mv.visitVarInsn(ALOAD, 0);
+    mv.visitInsn(MONITOREXIT);
+    mv.visitVarInsn(ALOAD, 0);
+    mv.visitInsn(MONITOREXIT);
+    mv.visitInsn(RETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+
+  // Returns 1 if exiting the monitor of a new object throws IllegalMonitorStateException
+  private void catchUnowned() {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "catchUnowned", "()I", null, null);
+    Label start = new Label();
+    Label end = new Label();
+    Label handler = new Label();
+    mv.visitTryCatchBlock(start, end, handler, "java/lang/IllegalMonitorStateException");
+    mv.visitCode();
+    mv.visitLabel(start);
+    mv.visitTypeInsn(NEW, "java/lang/Object");
+    mv.visitInsn(DUP);
+    mv.visitMethodInsn(INVOKESPECIAL, "java/lang/Object", "<init>", "()V", false);
+    mv.visitInsn(MONITOREXIT);
+    mv.visitLabel(end);
+    mv.visitInsn(ICONST_0);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(handler);
+    mv.visitInsn(POP);
+    mv.visitInsn(ICONST_1);
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
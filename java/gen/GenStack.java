@@ -0,0 +1,95 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Stack.class, with a method for every form of the stack manipulation instructions. Each method pushes
+ * single digit ints (I) and longs (J), runs the instruction and returns the digits left on the stack from the bottom
+ * to the top as one number, e.g. 1, 2, swap gives 21.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenStack.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenStack java/Stack.class
+ */
+public class GenStack {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenStack gen = new GenStack();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "Stack", null, "java/lang/Object", null);
+
+    stack("pop", "II", POP, "I");
+    stack("pop2Form1", "III", POP2, "I");
+    stack("pop2Form2", "IJ", POP2, "I");
+    stack("dup", "II", DUP, "III");
+    stack("dupX1", "III", DUP_X1, "IIII");
+    stack("dupX2Form1", "IIII", DUP_X2, "IIIII");
+    stack("dupX2Form2", "IJI", DUP_X2, "IIJI");
+    stack("dup2Form1", "III", DUP2, "IIIII");
+    stack("dup2Form2", "IJ", DUP2, "IJJ");
+    stack("dup2X1Form1", "IIII", DUP2_X1, "IIIIII");
+    stack("dup2X1Form2", "IIJ", DUP2_X1, "IJIJ");
+    stack("dup2X2Form1", "IIIII", DUP2_X2, "IIIIIII");
+    stack("dup2X2Form2", "IIIJ", DUP2_X2, "IJIIJ");
+    stack("dup2X2Form3", "IJII", DUP2_X2, "IIIJII");
+    stack("dup2X2Form4", "IJJ", DUP2_X2, "IJJJ");
+    stack("swap", "III", SWAP, "III");
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  // Pushes the digits 1, 2, 3... with the given types, runs the instruction and folds the resulting stack into a number
+  private void stack(String name, String pushed, int opcode, String result) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, "()I", null, null);
+    mv.visitCode();
+
+    for (int i = 0; i < pushed.length(); i++) {
+      mv.visitIntInsn(BIPUSH, i + 1);
+      if (pushed.charAt(i) == 'J') {
+        mv.visitInsn(I2L);
+      }
+    }
+
+    mv.visitInsn(opcode);
+
+    // Saves the stack to local variables, the top first
+    int[] slots = new int[result.length()];
+    int slot = 0;
+    for (int i = result.length() - 1; i >= 0; i--) {
+      slots[i] = slot;
+      if (result.charAt(i) == 'J') {
+        mv.visitVarInsn(LSTORE, slot);
+        slot += 2;
+      } else {
+        mv.visitVarInsn(ISTORE, slot);
+        slot += 1;
+      }
+    }
+
+    // result = result * 10 + digit, from the bottom of the stack to the top
+    mv.visitInsn(ICONST_0);
+    for (int i = 0; i < result.length(); i++) {
+      mv.visitIntInsn(BIPUSH, 10);
+      mv.visitInsn(IMUL);
+      if (result.charAt(i) == 'J') {
+        mv.visitVarInsn(LLOAD, slots[i]);
+        mv.visitInsn(L2I);
+      } else {
+        mv.visitVarInsn(ILOAD, slots[i]);
+      }
+      mv.visitInsn(IADD);
+    }
+
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
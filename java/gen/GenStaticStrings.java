@@ -0,0 +1,39 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/StaticStrings.class, which reads its String constant field with getstatic. javac inlines constant
+ * fields wherever they are used, so the field would only be read reflectively.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenStaticStrings.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenStaticStrings java/StaticStrings.class
+ */
+public class GenStaticStrings {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenStaticStrings gen = new GenStaticStrings();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "StaticStrings", null, "java/lang/Object", null);
+
+    cw.visitField(ACC_PUBLIC | ACC_STATIC | ACC_FINAL, "GREETING", "Ljava/lang/String;", null, "hello").visitEnd();
+
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "greeting", "()Ljava/lang/String;", null, null);
+    mv.visitCode();
+    mv.visitFieldInsn(GETSTATIC, "StaticStrings", "GREETING", "Ljava/lang/String;");
+    mv.visitInsn(ARETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+}
@@ -0,0 +1,150 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Subroutines.class, a Java 1.4 class implementing finally blocks with jsr/ret the way javac used to,
+ * and java/SubroutinesModern.class, which uses jsr in a Java 6 class file.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenSubroutines.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenSubroutines java/Subroutines.class java/SubroutinesModern.class
+ */
+public class GenSubroutines {
+
+  private static final int JSR_W = 201;
+  private static final int WIDE = 196;
+
+  public static void main(String[] args) throws Exception {
+    Files.write(Path.of(args[0]), new GenSubroutines().generate("Subroutines", V1_4));
+    Files.write(Path.of(args[1]), new GenSubroutines().generate("SubroutinesModern", V1_6));
+  }
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  private byte[] generate(String name, int version) {
+    cw.visit(version, ACC_PUBLIC | ACC_SUPER, name, null, "java/lang/Object", null);
+
+    // int x = a * 2;
+    // try { return x; } finally { x += 1; }
+    // The finally subroutine runs before returning the value saved beforehand, so the result is a * 2
+    MethodVisitor mv = method("finallyReturn");
+    Label finallyBlock = new Label();
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(ICONST_2);
+    mv.visitInsn(IMUL);
+    mv.visitVarInsn(ISTORE, 1);
+    mv.visitVarInsn(ILOAD, 1);
+    mv.visitVarInsn(ISTORE, 2);
+    mv.visitJumpInsn(JSR, finallyBlock);
+    mv.visitVarInsn(ILOAD, 2);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(finallyBlock);
+    mv.visitVarInsn(ASTORE, 3);
+    mv.visitIincInsn(1, 1);
+    mv.visitVarInsn(RET, 3);
+    end(mv);
+
+    // int x = a;
+    // for (int i = 0; i < 3; i++) { try { } finally { x *= 2; } }
+    // return x;
+    // The same subroutine is called from a loop, returning to the same place every time
+    mv = method("loop");
+    Label check = new Label();
+    Label body = new Label();
+    Label subroutine = new Label();
+    mv.visitInsn(ICONST_0);
+    mv.visitVarInsn(ISTORE, 1);
+    mv.visitJumpInsn(GOTO, check);
+    mv.visitLabel(body);
+    mv.visitJumpInsn(JSR, subroutine);
+    mv.visitIincInsn(1, 1);
+    mv.visitLabel(check);
+    mv.visitVarInsn(ILOAD, 1);
+    mv.visitInsn(ICONST_3);
+    mv.visitJumpInsn(IF_ICMPLT, body);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(subroutine);
+    mv.visitVarInsn(ASTORE, 2);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(ICONST_2);
+    mv.visitInsn(IMUL);
+    mv.visitVarInsn(ISTORE, 0);
+    mv.visitVarInsn(RET, 2);
+    end(mv);
+
+    // Nested subroutines using jsr_w and wide ret, returns a + 3
+    mv = method("nested");
+    Label outer = new Label();
+    Label inner = new Label();
+    jsrW(mv, outer);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(outer);
+    mv.visitVarInsn(ASTORE, 1);
+    mv.visitIincInsn(0, 1);
+    jsrW(mv, inner);
+    mv.visitIincInsn(0, 1);
+    wideRet(mv, 1);
+    mv.visitLabel(inner);
+    mv.visitVarInsn(ASTORE, 2);
+    mv.visitIincInsn(0, 1);
+    mv.visitVarInsn(RET, 2);
+    end(mv);
+
+    cw.visitEnd();
+    return patch(cw.toByteArray());
+  }
+
+  private MethodVisitor method(String name) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, "(I)I", null, null);
+    mv.visitCode();
+    return mv;
+  }
+
+  private static void end(MethodVisitor mv) {
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+
+  // ASM never emits jsr_w, so it is written as jsr followed by iconst_m1 and pop and patched by patch()
+  private static void jsrW(MethodVisitor mv, Label target) {
+    mv.visitJumpInsn(JSR, target);
+    mv.visitInsn(ICONST_M1);
+    mv.visitInsn(POP);
+  }
+
+  // ASM only emits wide ret for indices above 255, so it is written as ret followed by iconst_m1 and pop and patched by patch()
+  private static void wideRet(MethodVisitor mv, int index) {
+    mv.visitVarInsn(RET, index);
+    mv.visitInsn(ICONST_M1);
+    mv.visitInsn(POP);
+  }
+
+  // Turns the placeholders into the instructions of the same length, the offset of jsr_w is relative to the same
+  // instruction as the one of the jsr. No other place in the class file holds these byte sequences.
+  private static byte[] patch(byte[] bytes) {
+    for (int i = 0; i + 4 < bytes.length; i++) {
+      int opcode = bytes[i] & 0xFF;
+
+      if (opcode == JSR && bytes[i + 3] == ICONST_M1 && bytes[i + 4] == POP) {
+        byte sign = (byte) (bytes[i + 1] < 0 ? 0xFF : 0);
+        bytes[i + 4] = bytes[i + 2];
+        bytes[i + 3] = bytes[i + 1];
+        bytes[i + 2] = sign;
+        bytes[i + 1] = sign;
+        bytes[i] = (byte) JSR_W;
+      } else if (opcode == RET && bytes[i + 2] == ICONST_M1 && bytes[i + 3] == POP) {
+        bytes[i + 3] = bytes[i + 1];
+        bytes[i + 2] = 0;
+        bytes[i + 1] = (byte) RET;
+        bytes[i] = (byte) WIDE;
+      }
+    }
+    return bytes;
+  }
+}
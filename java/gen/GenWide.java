@@ -0,0 +1,77 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+import jdk.internal.org.objectweb.asm.Type;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/Wide.class, whose methods use local variables above 255 like generated code with hundreds of locals.
+ * It is a Java 1.4 class so that it may contain subroutines.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenWide.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenWide java/Wide.class
+ */
+public class GenWide {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenWide gen = new GenWide();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V1_4, ACC_PUBLIC | ACC_SUPER, "Wide", null, "java/lang/Object", null);
+
+    // Each method stores its argument to a local variable above 255 and loads it back
+    roundTrip("ints", Type.INT_TYPE);
+    roundTrip("longs", Type.LONG_TYPE);
+    roundTrip("floats", Type.FLOAT_TYPE);
+    roundTrip("doubles", Type.DOUBLE_TYPE);
+    roundTrip("refs", Type.getType(Object.class));
+
+    // a += 1000; a -= 32768; a += 32767; return a;
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "increment", "(I)I", null, null);
+    mv.visitCode();
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitVarInsn(ISTORE, 300);
+    mv.visitIincInsn(300, 1000);
+    mv.visitIincInsn(300, -32768);
+    mv.visitIincInsn(300, 32767);
+    mv.visitVarInsn(ILOAD, 300);
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+
+    // A subroutine saving its return address to local 300, returns a + 1
+    mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "subroutine", "(I)I", null, null);
+    mv.visitCode();
+    Label subroutine = new Label();
+    mv.visitJumpInsn(JSR, subroutine);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(IRETURN);
+    mv.visitLabel(subroutine);
+    mv.visitVarInsn(ASTORE, 300);
+    mv.visitIincInsn(0, 1);
+    mv.visitVarInsn(RET, 300);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  private void roundTrip(String name, Type type) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, Type.getMethodDescriptor(type, type), null, null);
+    mv.visitCode();
+    mv.visitVarInsn(type.getOpcode(ILOAD), 0);
+    mv.visitVarInsn(type.getOpcode(ISTORE), 400);
+    mv.visitVarInsn(type.getOpcode(ILOAD), 400);
+    mv.visitInsn(type.getOpcode(IRETURN));
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
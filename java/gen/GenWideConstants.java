@@ -0,0 +1,49 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+import jdk.internal.org.objectweb.asm.Type;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/WideConstants.class, whose constant pool is filled with 300 unused ints so that the constants loaded
+ * by its methods are above index 255 and need ldc_w.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenWideConstants.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenWideConstants java/WideConstants.class
+ */
+public class GenWideConstants {
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenWideConstants gen = new GenWideConstants();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private byte[] generate() {
+    cw.visit(V11, ACC_PUBLIC | ACC_SUPER, "WideConstants", null, "java/lang/Object", null);
+
+    for (int i = 0; i < 300; i++) {
+      cw.newConst(100_000 + i);
+    }
+
+    load("wideInt", "()I", 7_654_321);
+    load("wideFloat", "()F", 2.5f);
+    load("wideString", "()Ljava/lang/String;", "hello");
+    load("wideClass", "()Ljava/lang/Object;", Type.getObjectType("java/lang/String"));
+
+    cw.visitEnd();
+    return cw.toByteArray();
+  }
+
+  private void load(String name, String desc, Object constant) {
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, name, desc, null, null);
+    mv.visitCode();
+    mv.visitLdcInsn(constant);
+    mv.visitInsn(Type.getReturnType(desc).getOpcode(IRETURN));
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+  }
+}
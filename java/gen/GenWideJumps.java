@@ -0,0 +1,104 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import jdk.internal.org.objectweb.asm.ClassWriter;
+import jdk.internal.org.objectweb.asm.Label;
+import jdk.internal.org.objectweb.asm.MethodVisitor;
+
+import static jdk.internal.org.objectweb.asm.Opcodes.*;
+
+/**
+ * Generates java/WideJumps.class, javac only emits goto_w for jumps in methods of more than 32KB.
+ *
+ * javac --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -d java/gen java/gen/GenWideJumps.java
+ * java --add-exports java.base/jdk.internal.org.objectweb.asm=ALL-UNNAMED -cp java/gen GenWideJumps java/WideJumps.class
+ */
+public class GenWideJumps {
+
+  private static final int ISTORE_1 = 60;
+  private static final int GOTO_W = 200;
+
+  private final ClassWriter cw = new ClassWriter(ClassWriter.COMPUTE_MAXS);
+
+  public static void main(String[] args) throws Exception {
+    GenWideJumps gen = new GenWideJumps();
+    Files.write(Path.of(args[0]), gen.generate());
+  }
+
+  private final List<Label[]> wideGotos = new ArrayList<>();
+
+  private byte[] generate() {
+    cw.visit(V1_6, ACC_PUBLIC | ACC_SUPER, "WideJumps", null, "java/lang/Object", null);
+
+    // Sums the numbers up to the argument, jumping forwards and backwards with goto_w:
+    //
+    //   int sum = 0;
+    //   goto_w check
+    //   loop: sum += n; n--;
+    //   check: if (n <= 0) goto done
+    //   goto_w loop
+    //   done: return sum;
+    MethodVisitor mv = cw.visitMethod(ACC_PUBLIC | ACC_STATIC, "sumTo", "(I)I", null, null);
+    mv.visitCode();
+
+    Label loop = new Label();
+    Label check = new Label();
+    Label done = new Label();
+
+    mv.visitInsn(ICONST_0);
+    mv.visitVarInsn(ISTORE, 1);
+    wideGoto(mv, check);
+
+    mv.visitLabel(loop);
+    mv.visitVarInsn(ILOAD, 1);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitInsn(IADD);
+    mv.visitVarInsn(ISTORE, 1);
+    mv.visitIincInsn(0, -1);
+
+    mv.visitLabel(check);
+    mv.visitVarInsn(ILOAD, 0);
+    mv.visitJumpInsn(IFLE, done);
+    wideGoto(mv, loop);
+
+    mv.visitLabel(done);
+    mv.visitVarInsn(ILOAD, 1);
+    mv.visitInsn(IRETURN);
+    mv.visitMaxs(0, 0);
+    mv.visitEnd();
+
+    cw.visitEnd();
+    byte[] bytes = cw.toByteArray();
+
+    // The code of sumTo is the only place starting with iconst_0, istore_1, goto
+    int code = 0;
+    while (!(bytes[code] == ICONST_0 && bytes[code + 1] == ISTORE_1 && (bytes[code + 2] & 0xFF) == GOTO)) {
+      code++;
+    }
+
+    for (Label[] wideGoto : wideGotos) {
+      int at = wideGoto[0].getOffset();
+      int offset = wideGoto[1].getOffset() - at;
+
+      bytes[code + at] = (byte) GOTO_W;
+      bytes[code + at + 1] = (byte) (offset >> 24);
+      bytes[code + at + 2] = (byte) (offset >> 16);
+      bytes[code + at + 3] = (byte) (offset >> 8);
+      bytes[code + at + 4] = (byte) offset;
+    }
+
+    return bytes;
+  }
+
+  // ASM only emits goto_w for offsets which do not fit into 16 bits, so a goto followed by two nops is written and
+  // patched into a goto_w of the same length once the offsets are known
+  private void wideGoto(MethodVisitor mv, Label target) {
+    Label at = new Label();
+    mv.visitLabel(at);
+    mv.visitJumpInsn(GOTO, target);
+    mv.visitInsn(NOP);
+    mv.visitInsn(NOP);
+    wideGotos.add(new Label[] { at, target });
+  }
+}
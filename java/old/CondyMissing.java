@@ -0,0 +1,7 @@
+// Only exists when CondyBootstraps is compiled, so that creating it fails with NoClassDefFoundError at run time:
+//
+//   javac -d java java/old/CondyMissing.java
+//   javac -d java -cp java java/CondyBootstraps.java
+//   rm java/CondyMissing.class
+public class CondyMissing {
+}
@@ -0,0 +1,7 @@
+// Only exists when Constants is compiled, so that loading its class constant fails with NoClassDefFoundError:
+//
+//   javac -d java java/old/ConstantsMissing.java
+//   javac -d java -cp java java/Constants.java java/ConstantsOther.java
+//   rm java/ConstantsMissing.class
+public class ConstantsMissing {
+}
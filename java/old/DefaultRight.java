@@ -0,0 +1,8 @@
+// Older version of java/DefaultRight.java without any methods, DefaultBoth is compiled against it:
+//
+//   javac -d java java/old/DefaultRight.java
+//   javac -d java -cp java java/DefaultLeft.java java/DefaultBoth.java
+//   javac -d java -cp java java/DefaultRight.java java/DefaultConflicts.java
+interface DefaultRight {
+
+}
@@ -0,0 +1,8 @@
+// Only exists when Exceptions is compiled, so that resolving the catch type of its handler fails with
+// NoClassDefFoundError:
+//
+//   javac -d java java/old/ExceptionsMissing.java
+//   javac -d java -cp java java/Exceptions.java
+//   rm java/ExceptionsMissing.class
+public class ExceptionsMissing extends RuntimeException {
+}
@@ -0,0 +1,12 @@
+// Older version of java/FinalBase.java where value() is not final yet, FinalOverride is compiled against it:
+//
+//   javac -d java java/old/FinalBase.java
+//   javac -d java -cp java java/FinalOverride.java
+//   javac -d java java/FinalBase.java
+public class FinalBase {
+
+  int value() {
+    return 1;
+  }
+
+}
@@ -0,0 +1,12 @@
+// Older version of java/LinkageBase.java, LinkageImpl and LinkageRemoved are compiled against it:
+//
+//   javac -d java java/old/LinkageBase.java
+//   javac -d java -cp java java/LinkageImpl.java java/LinkageRemoved.java
+//   javac -d java -cp java java/LinkageBase.java java/Linkage.java
+abstract class LinkageBase {
+
+  static int removed() {
+    return 1;
+  }
+
+}
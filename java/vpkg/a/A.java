@@ -0,0 +1,17 @@
+package vpkg.a;
+
+public class A {
+
+  public static A create() {
+    return new A();
+  }
+
+  int m() {
+    return 1;
+  }
+
+  public static int callM(A a) {
+    return a.m();
+  }
+
+}
@@ -0,0 +1,14 @@
+package vpkg.a;
+
+public class C extends vpkg.b.B {
+
+  public static C create() {
+    return new C();
+  }
+
+  // overrides A.m(), which is in the same package, but not B.m()
+  int m() {
+    return 3;
+  }
+
+}
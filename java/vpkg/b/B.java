@@ -0,0 +1,18 @@
+package vpkg.b;
+
+public class B extends vpkg.a.A {
+
+  public static vpkg.a.A create() {
+    return new B();
+  }
+
+  // A.m() is package private in another package, this does not override it
+  int m() {
+    return 2;
+  }
+
+  public static int callM(B b) {
+    return b.m();
+  }
+
+}
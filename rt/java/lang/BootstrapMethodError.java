@@ -0,0 +1,12 @@
+package java.lang;
+
+public class BootstrapMethodError extends LinkageError {
+
+  public BootstrapMethodError() {
+  }
+
+  public BootstrapMethodError(String message) {
+    super(message);
+  }
+
+}
@@ -0,0 +1,16 @@
+package java.lang;
+
+public final class Class<T> {
+
+  private final String name;
+
+  // Mirrors are created by the VM, which fills in the name directly
+  private Class(String name) {
+    this.name = name;
+  }
+
+  public String getName() {
+    return name;
+  }
+
+}
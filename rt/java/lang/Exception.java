@@ -0,0 +1,16 @@
+package java.lang;
+
+public class Exception extends Throwable {
+
+  public Exception() {
+  }
+
+  public Exception(String message) {
+    super(message);
+  }
+
+  public Exception(String message, Throwable cause) {
+    super(message, cause);
+  }
+
+}
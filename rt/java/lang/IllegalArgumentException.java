@@ -0,0 +1,16 @@
+package java.lang;
+
+public class IllegalArgumentException extends RuntimeException {
+
+  public IllegalArgumentException() {
+  }
+
+  public IllegalArgumentException(String message) {
+    super(message);
+  }
+
+  public IllegalArgumentException(String message, Throwable cause) {
+    super(message, cause);
+  }
+
+}
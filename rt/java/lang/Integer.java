@@ -0,0 +1,19 @@
+package java.lang;
+
+public final class Integer {
+
+  private final int value;
+
+  public Integer(int value) {
+    this.value = value;
+  }
+
+  public static Integer valueOf(int i) {
+    return new Integer(i);
+  }
+
+  public int intValue() {
+    return value;
+  }
+
+}
@@ -0,0 +1,14 @@
+package java.lang;
+
+public class Object {
+
+  public Object() {
+  }
+
+  public native int hashCode();
+
+  public native String toString();
+
+  protected native Object clone() throws CloneNotSupportedException;
+
+}
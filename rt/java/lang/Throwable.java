@@ -0,0 +1,29 @@
+package java.lang;
+
+public class Throwable implements java.io.Serializable {
+
+  private String detailMessage;
+
+  private Throwable cause;
+
+  public Throwable() {
+  }
+
+  public Throwable(String message) {
+    this.detailMessage = message;
+  }
+
+  public Throwable(String message, Throwable cause) {
+    this.detailMessage = message;
+    this.cause = cause;
+  }
+
+  public String getMessage() {
+    return detailMessage;
+  }
+
+  public Throwable getCause() {
+    return cause;
+  }
+
+}
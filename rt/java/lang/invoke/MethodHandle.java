@@ -0,0 +1,33 @@
+package java.lang.invoke;
+
+public abstract class MethodHandle {
+
+  private final MethodType type;
+
+  // The symbolic reference the handle was resolved from, see CONSTANT_MethodHandle_info
+  private final int referenceKind;
+  private final String owner;
+  private final String name;
+  private final String descriptor;
+  private final boolean isInterface;
+
+  // Method handles are created by the VM when resolving CONSTANT_MethodHandle entries
+  private MethodHandle(MethodType type, int referenceKind, String owner, String name, String descriptor, boolean isInterface) {
+    this.type = type;
+    this.referenceKind = referenceKind;
+    this.owner = owner;
+    this.name = name;
+    this.descriptor = descriptor;
+    this.isInterface = isInterface;
+  }
+
+  public MethodType type() {
+    return type;
+  }
+
+  // Signature polymorphic, the VM invokes the handle with the descriptor of the call site
+  public final native Object invokeExact(Object... args) throws Throwable;
+
+  public final native Object invoke(Object... args) throws Throwable;
+
+}
@@ -0,0 +1,23 @@
+package java.lang.invoke;
+
+public class MethodHandles {
+
+  private MethodHandles() {
+  }
+
+  public static final class Lookup {
+
+    private final Class<?> lookupClass;
+
+    // Lookups are created by the VM for the class whose bootstrap method it calls
+    private Lookup(Class<?> lookupClass) {
+      this.lookupClass = lookupClass;
+    }
+
+    public Class<?> lookupClass() {
+      return lookupClass;
+    }
+
+  }
+
+}
@@ -0,0 +1,16 @@
+package java.lang.invoke;
+
+public final class MethodType {
+
+  private final String descriptor;
+
+  // Method types are created by the VM when resolving CONSTANT_MethodType entries
+  private MethodType(String descriptor) {
+    this.descriptor = descriptor;
+  }
+
+  public String toMethodDescriptorString() {
+    return descriptor;
+  }
+
+}
@@ -0,0 +1,6 @@
+package java.util;
+
+@FunctionalInterface
+public interface Comparator<T> {
+  int compare(T o1, T o2);
+}
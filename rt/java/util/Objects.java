@@ -0,0 +1,10 @@
+package java.util;
+
+public final class Objects {
+
+  private Objects() {
+  }
+
+  public static native <T> T requireNonNull(T obj);
+
+}
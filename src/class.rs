@@ -8,6 +8,42 @@ pub struct StaticMethod {
     pub class_name: Rc<str>,
     pub method_name: Rc<str>,
     pub method_desc: Rc<str>,
+    // true if resolved from an InterfaceMethodref
+    pub is_interface: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodHandleRef {
+    // one of the REF_ kinds, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.5
+    pub kind: u8,
+    pub class_name: Rc<str>,
+    pub name: Rc<str>,
+    pub desc: Rc<str>,
+    pub is_interface: bool,
+}
+
+#[derive(Debug)]
+pub struct BootstrapMethod {
+    pub method_ref: u16,
+    pub arguments: Vec<u16>,
+}
+
+/// An entry of a method's exception table, the pcs are offsets into the method's code, see
+/// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.7.3
+#[derive(Debug)]
+pub struct ExceptionHandler {
+    pub start_pc: usize,
+    pub end_pc: usize,
+    pub handler_pc: usize,
+    // Zero for handlers catching everything, e.g. finally blocks
+    pub catch_type: u16,
+}
+
+#[derive(Debug)]
+pub struct FieldRef {
+    pub class_name: Rc<str>,
+    pub field_name: Rc<str>,
+    pub field_desc: Rc<str>,
 }
 
 #[derive(Debug)]
@@ -19,6 +55,12 @@ pub enum Const {
 
     NameType(u16, u16),
     FieldMethod(u16, u16),
+    InterfaceMethod(u16, u16),
+
+    MethodHandle(u8, u16),
+    MethodType(u16),
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
 
     Integer(i32),
     Long(i64),
@@ -32,9 +74,9 @@ pub enum Const {
     Unknown,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ConstPool {
-    size: u16,
+    pub size: u16,
     table: Vec<Const>
 }
 
@@ -47,7 +89,34 @@ const CONSTANT_CLASS: u8 = 7;
 const CONSTANT_STRING: u8 = 8;
 const CONSTANT_FIELDREF: u8 = 9;
 const CONSTANT_METHODREF: u8 = 10;
+const CONSTANT_INTERFACEMETHODREF: u8 = 11;
 const CONSTANT_NAMEANDTYPE: u8 = 12;
+const CONSTANT_METHODHANDLE: u8 = 15;
+const CONSTANT_METHODTYPE: u8 = 16;
+const CONSTANT_DYNAMIC: u8 = 17;
+const CONSTANT_INVOKEDYNAMIC: u8 = 18;
+
+pub const ACC_PUBLIC: u16 = 0x0001;
+pub const ACC_PRIVATE: u16 = 0x0002;
+pub const ACC_PROTECTED: u16 = 0x0004;
+pub const ACC_STATIC: u16 = 0x0008;
+pub const ACC_FINAL: u16 = 0x0010;
+pub const ACC_VARARGS: u16 = 0x0080;
+pub const ACC_NATIVE: u16 = 0x0100;
+pub const ACC_INTERFACE: u16 = 0x0200;
+pub const ACC_ABSTRACT: u16 = 0x0400;
+pub const ACC_SYNTHETIC: u16 = 0x1000;
+
+// Method handle kinds, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.4.8
+pub const REF_GET_FIELD: u8 = 1;
+pub const REF_GET_STATIC: u8 = 2;
+pub const REF_PUT_FIELD: u8 = 3;
+pub const REF_PUT_STATIC: u8 = 4;
+pub const REF_INVOKE_VIRTUAL: u8 = 5;
+pub const REF_INVOKE_STATIC: u8 = 6;
+pub const REF_INVOKE_SPECIAL: u8 = 7;
+pub const REF_NEW_INVOKE_SPECIAL: u8 = 8;
+pub const REF_INVOKE_INTERFACE: u8 = 9;
 
 impl ConstPool {
     fn load(r: &mut ClassFileReader) -> ConstPool {
@@ -71,7 +140,12 @@ impl ConstPool {
                 CONSTANT_CLASS => Const::ClassIndex(r.u2()),
                 CONSTANT_STRING => Const::StringIndex(r.u2()),
                 CONSTANT_FIELDREF | CONSTANT_METHODREF => Const::FieldMethod(r.u2(), r.u2()),
+                CONSTANT_INTERFACEMETHODREF => Const::InterfaceMethod(r.u2(), r.u2()),
                 CONSTANT_NAMEANDTYPE => Const::NameType(r.u2(), r.u2()),
+                CONSTANT_METHODHANDLE => Const::MethodHandle(r.u1(), r.u2()),
+                CONSTANT_METHODTYPE => Const::MethodType(r.u2()),
+                CONSTANT_DYNAMIC => Const::Dynamic(r.u2(), r.u2()),
+                CONSTANT_INVOKEDYNAMIC => Const::InvokeDynamic(r.u2(), r.u2()),
                 CONSTANT_DOUBLE => {
                     let bytes = r.u8();
 
@@ -105,10 +179,7 @@ impl ConstPool {
             println!("resolved {:?}", c);
             i += 1;
 
-            let takes_two_entries = match c {
-                Const::Double(_) | Const::Long(_) => true,
-                _ => false
-            };
+            let takes_two_entries = matches!(c, Const::Double(_) | Const::Long(_));
 
             table.push(c);
             if takes_two_entries {
@@ -128,6 +199,68 @@ impl ConstPool {
     }
 
 
+    pub fn resolve_string(&self, idx: usize) -> Result<Rc<str>> {
+        match self.table.get(idx - 1) {
+            Some(Const::StringIndex(i)) => self.resolve_str(*i as usize),
+            _ => Err(anyhow!("index {} does not point to StringIndex", idx))
+        }
+    }
+
+    /// Returns the bootstrap method index together with the name and descriptor of the call site.
+    pub fn resolve_invoke_dynamic(&self, idx: usize) -> Result<(u16, Rc<str>, Rc<str>)> {
+        match self.table.get(idx - 1) {
+            Some(Const::InvokeDynamic(bootstrap_idx, name_type_idx)) => {
+                let (name, desc) = self.resolve_name_type(*name_type_idx as usize)?;
+                Ok((*bootstrap_idx, name, desc))
+            },
+            _ => Err(anyhow!("index {} does not point to InvokeDynamic", idx))
+        }
+    }
+
+    /// Returns the bootstrap method index together with the name and field descriptor of a dynamically-computed constant.
+    pub fn resolve_dynamic(&self, idx: usize) -> Result<(u16, Rc<str>, Rc<str>)> {
+        match self.table.get(idx - 1) {
+            Some(Const::Dynamic(bootstrap_idx, name_type_idx)) => {
+                let (name, desc) = self.resolve_name_type(*name_type_idx as usize)?;
+                Ok((*bootstrap_idx, name, desc))
+            },
+            _ => Err(anyhow!("index {} does not point to Dynamic", idx))
+        }
+    }
+
+    pub fn resolve_method_handle(&self, idx: usize) -> Result<MethodHandleRef> {
+        match self.table.get(idx - 1) {
+            Some(Const::MethodHandle(kind, ref_idx)) => {
+                let (class_name, name, desc) = self.resolve_member(*ref_idx as usize)?;
+                let is_interface = matches!(self.table.get(*ref_idx as usize - 1), Some(Const::InterfaceMethod(_, _)));
+                Ok(MethodHandleRef { kind: *kind, class_name, name, desc, is_interface })
+            },
+            _ => Err(anyhow!("index {} does not point to MethodHandle", idx))
+        }
+    }
+
+    /// Returns the method descriptor of a MethodType constant.
+    pub fn resolve_method_type(&self, idx: usize) -> Result<Rc<str>> {
+        match self.table.get(idx - 1) {
+            Some(Const::MethodType(desc_idx)) => self.resolve_str(*desc_idx as usize),
+            _ => Err(anyhow!("index {} does not point to MethodType", idx))
+        }
+    }
+
+    fn resolve_name_type(&self, idx: usize) -> Result<(Rc<str>, Rc<str>)> {
+        match self.table.get(idx - 1) {
+            Some(Const::NameType(name_idx, type_idx)) => Ok((self.resolve_str(*name_idx as usize)?, self.resolve_str(*type_idx as usize)?)),
+            _ => Err(anyhow!("index {} does not point to NameType", idx))
+        }
+    }
+
+    pub fn resolve_class_name(&self, idx: usize) -> Result<Rc<str>> {
+        match self.table.get(idx - 1) {
+            Some(Const::ClassIndex(i)) => self.resolve_str(*i as usize),
+            _ => Err(anyhow!("index {} does not point to ClassIndex", idx))
+        }
+    }
+
     fn resolve_str(&self, idx: usize) -> Result<Rc<str>> {
         let c = self.table.get(idx - 1);
 
@@ -144,8 +277,21 @@ impl ConstPool {
     }
 
     pub fn resolve_static_method(&self, idx: usize) -> Result<StaticMethod> {
+        let (class_name, method_name, method_desc) = self.resolve_member(idx)?;
+        let is_interface = matches!(self.table.get(idx - 1), Some(Const::InterfaceMethod(_, _)));
+
+        Ok(StaticMethod { class_name, method_name, method_desc, is_interface })
+    }
+
+    pub fn resolve_field(&self, idx: usize) -> Result<FieldRef> {
+        let (class_name, field_name, field_desc) = self.resolve_member(idx)?;
+
+        Ok(FieldRef { class_name, field_name, field_desc })
+    }
+
+    fn resolve_member(&self, idx: usize) -> Result<(Rc<str>, Rc<str>, Rc<str>)> {
         match self.table.get(idx - 1) {
-            Some(Const::FieldMethod(class_idx, name_type_index)) => {
+            Some(Const::FieldMethod(class_idx, name_type_index)) | Some(Const::InterfaceMethod(class_idx, name_type_index)) => {
                 let class_name = match self.table.get(*class_idx as usize - 1) {
                     Some(Const::ClassIndex(idx)) => {
                         match self.table.get(*idx as usize - 1) {
@@ -156,7 +302,7 @@ impl ConstPool {
                     Some(_) | None => return Err(anyhow!("class index does not point to ClassIndex"))
                 };
 
-                let (method_name, method_desc) = match self.table.get(*name_type_index as usize - 1) {
+                let (name, desc) = match self.table.get(*name_type_index as usize - 1) {
                     Some(Const::NameType(name_idx, type_idx)) => {
 
                         let name = match self.table.get(*name_idx as usize - 1) {
@@ -174,11 +320,7 @@ impl ConstPool {
                     Some(_) | None => return Err(anyhow!("name_type_idx does not point to NameType"))
                 };
 
-                Ok(StaticMethod {
-                    class_name: class_name.clone(),
-                    method_name: method_name.clone(),
-                    method_desc: method_desc.clone(),
-                })
+                Ok((class_name.clone(), name.clone(), desc.clone()))
             },
             _ => Err(anyhow!("index does not point to FieldMethod"))
         }
@@ -187,26 +329,111 @@ impl ConstPool {
 
 #[derive(Debug)]
 pub struct Class {
-    version_major: u16,
-    version_minor: u16,
+    pub version_major: u16,
+    pub version_minor: u16,
     pub const_pool: ConstPool,
     pub name: Rc<str>,
-    super_class: Rc<str>,
-    flags: u16,
-    interfaces: Vec<Rc<str>>,
-    fields: Vec<Field>,
+    pub super_class: Option<Rc<str>>,
+    pub flags: u16,
+    pub interfaces: Vec<Rc<str>>,
+    pub fields: Vec<Field>,
     pub methods: Vec<Field>,
-    pub attributes: Vec<Attribute>
+    pub attributes: Vec<Attribute>,
+    pub bootstrap_methods: Vec<BootstrapMethod>,
+}
+
+impl Class {
+    pub fn is_interface(&self) -> bool {
+        self.flags & ACC_INTERFACE != 0
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.flags & ACC_ABSTRACT != 0
+    }
+
+    /// Name of the runtime package, the class name up to the last '/'
+    pub fn package_name(&self) -> &str {
+        match self.name.rfind('/') {
+            Some(i) => &self.name[..i],
+            None => ""
+        }
+    }
+
+    pub fn find_method(&self, name: &str, desc: &str) -> Option<usize> {
+        self.methods.iter().position(|m| m.name.as_ref() == name && m.descriptor.as_ref() == desc)
+    }
 }
 
+// Fields and methods share the same structure in the class file, so both are represented by Field
 #[derive(Debug)]
 pub struct Field {
-    flags: u16,
+    pub flags: u16,
     pub name: Rc<str>,
-    descriptor: Rc<str>,
+    pub descriptor: Rc<str>,
     pub attributes: Vec<Attribute>
 }
 
+impl Field {
+    pub fn is_static(&self) -> bool {
+        self.flags & ACC_STATIC != 0
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.flags & ACC_PUBLIC != 0
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.flags & ACC_PRIVATE != 0
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.flags & ACC_PROTECTED != 0
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.flags & ACC_FINAL != 0
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.flags & ACC_ABSTRACT != 0
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.flags & ACC_NATIVE != 0
+    }
+
+    pub fn is_varargs(&self) -> bool {
+        self.flags & ACC_VARARGS != 0
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|a| a.name.as_ref() == name)
+    }
+
+    /// The exception handlers of a method in the order they are searched, following its code in the Code attribute.
+    pub fn exception_table(&self) -> Vec<ExceptionHandler> {
+        let data = match self.attribute("Code") {
+            Some(a) => &a.data,
+            None => return Vec::new()
+        };
+
+        let u2 = |pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+
+        let code_length = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let pos = 8 + code_length;
+
+        (0..u2(pos) as usize)
+            .map(|i| pos + 2 + 8 * i)
+            .map(|entry| ExceptionHandler {
+                start_pc: u2(entry) as usize,
+                end_pc: u2(entry + 2) as usize,
+                handler_pc: u2(entry + 4) as usize,
+                catch_type: u2(entry + 6),
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct Attribute {
     pub name: Rc<str>,
@@ -257,10 +484,30 @@ impl ClassFileReader {
     fn string(&mut self, count: i32) -> String {
         let mut buf = vec![0u8; count as usize];
         self.class_file.read_exact(&mut buf).expect("must read");
-        String::from_utf8(buf).expect("must convert!")
+        decode_modified_utf8(&buf)
     }
 }
 
+// Class files store strings in modified UTF-8, which encodes NUL in two bytes and supplementary characters as two
+// separately encoded surrogates, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.4.7
+fn decode_modified_utf8(bytes: &[u8]) -> String {
+    let mut chars = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let (c, length) = match bytes[i] {
+            b if b & 0x80 == 0 => (b as u16, 1),
+            b if b & 0xE0 == 0xC0 => (((b as u16 & 0x1F) << 6) | (bytes[i + 1] as u16 & 0x3F), 2),
+            b => (((b as u16 & 0x0F) << 12) | ((bytes[i + 1] as u16 & 0x3F) << 6) | (bytes[i + 2] as u16 & 0x3F), 3)
+        };
+
+        chars.push(c);
+        i += length;
+    }
+
+    String::from_utf16_lossy(&chars)
+}
+
 pub fn load(path: &str) -> Result<Class> {
     let mut r = ClassFileReader::new(path)?;
 
@@ -268,27 +515,41 @@ pub fn load(path: &str) -> Result<Class> {
         return Err(anyhow!("not a java file"));
     }
 
-    let version_major = r.u2();
     let version_minor = r.u2();
+    let version_major = r.u2();
 
     let const_pool = ConstPool::load(&mut r);
 
-    let class = Class {
+    let mut class = Class {
         version_major,
         version_minor,
         flags: r.u2(),
         name: const_pool.resolve_str(r.u2() as usize).with_context(|| "error while resolving class name")?,
-        super_class: const_pool.resolve_str(r.u2() as usize).with_context(|| "error while resolving super class name")?,
+        super_class: super_class(&mut r, &const_pool)?,
         interfaces: interfaces(&mut r, &const_pool)?,
         fields: fields(&mut r, &const_pool)?,
         methods: fields(&mut r, &const_pool)?,
         attributes: attr(&mut r, &const_pool)?,
+        bootstrap_methods: Vec::new(),
         const_pool,
     };
 
+    class.bootstrap_methods = bootstrap_methods(&class.attributes);
+
     Ok(class)
 }
 
+fn super_class(r: &mut ClassFileReader, const_pool: &ConstPool) -> Result<Option<Rc<str>>> {
+    // java/lang/Object is the only class without a super class, its super_class item is zero
+    match r.u2() {
+        0 => Ok(None),
+        idx => {
+            let name = const_pool.resolve_str(idx as usize).with_context(|| "error while resolving super class name")?;
+            Ok(Some(name))
+        }
+    }
+}
+
 fn interfaces(r: &mut ClassFileReader, const_pool: &ConstPool) -> Result<Vec<Rc<str>>> {
     let count = r.u2();
     let mut v = Vec::new();
@@ -322,4 +583,27 @@ fn attr(reader: &mut ClassFileReader, const_pool: &ConstPool) -> Result<Vec<Attr
         v.push(Attribute { name, data });
     }
     Ok(v)
-}
\ No newline at end of file
+}
+
+// see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.7.23
+fn bootstrap_methods(attributes: &[Attribute]) -> Vec<BootstrapMethod> {
+    let data = match attributes.iter().find(|a| a.name.as_ref() == "BootstrapMethods") {
+        Some(a) => &a.data,
+        None => return Vec::new()
+    };
+
+    let u2 = |pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+
+    let count = u2(0);
+    let mut pos = 2;
+    let mut v = Vec::new();
+    for _ in 0..count {
+        let method_ref = u2(pos);
+        let num_arguments = u2(pos + 2) as usize;
+        let arguments = (0..num_arguments).map(|i| u2(pos + 4 + 2 * i)).collect();
+        pos += 4 + 2 * num_arguments;
+
+        v.push(BootstrapMethod { method_ref, arguments });
+    }
+    v
+}
@@ -1,21 +1,43 @@
-use crate::class::{Class, Const};
-use std::ops::{Deref, Add, Neg};
-use std::collections::HashMap;
+use crate::class::{Const, FieldRef, StaticMethod};
+use std::ops::Deref;
 
 use anyhow::{Result, anyhow};
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::os::macos::raw::stat;
-use std::borrow::Borrow;
 pub use crate::jvm::types::JTypeValue;
 use crate::jvm::objects::{Heap, Object, Array};
 use crate::jvm::frame::Frame;
-use crate::jvm::types::NULL_REF;
+use crate::jvm::arrays::array_size;
+use crate::jvm::types::{NULL_REF, param_descriptors};
+use crate::jvm::method_area::{MethodArea, RuntimeClass, ClassState, ResolvedMethod, Selected};
+pub use crate::jvm::exceptions::{JavaException, ThrownException};
+use crate::jvm::exceptions::{class_cast_message, ResolutionError, LINKAGE_ERROR, NO_CLASS_DEF_FOUND_ERROR, EXCEPTION_IN_INITIALIZER_ERROR};
+use crate::jvm::exceptions::{NO_SUCH_FIELD_ERROR, NO_SUCH_METHOD_ERROR, ABSTRACT_METHOD_ERROR, INCOMPATIBLE_CLASS_CHANGE_ERROR, NULL_POINTER_EXCEPTION, INSTANTIATION_ERROR, CLASS_CAST_EXCEPTION, ARITHMETIC_EXCEPTION, ARRAY_STORE_EXCEPTION, VERIFY_ERROR,
+                             ILLEGAL_MONITOR_STATE_EXCEPTION, STACK_OVERFLOW_ERROR};
 
 
 mod frame;
 mod types;
 mod objects;
+mod method_area;
+mod exceptions;
+mod natives;
+mod strings;
+mod invokedynamic;
+mod method_handles;
+mod mirrors;
+mod arrays;
+mod throwables;
+
+const OBJECT_CLASS: &str = "java/lang/Object";
+
+// Java frames are kept on the heap, only calls made by the VM itself (e.g. class initialization or method handles)
+// nest the interpreter on the native stack
+const MAX_STACK_DEPTH: usize = 16_384;
+
+// Native stack those nested calls may take up, half of what Rust gives a spawned thread by default. A debug build needs
+// about 30KB for each of them
+const MAX_NATIVE_STACK: usize = 1 << 20;
 
 const ACONST_NULL: u8 = 1;
 
@@ -49,6 +71,17 @@ const ILOAD_3: u8 = 29;
 const IALOAD: u8 = 46;
 const INEG: u8 = 116;
 const IADD: u8 = 96;
+const ISUB: u8 = 100;
+const IMUL: u8 = 104;
+const IDIV: u8 = 108;
+const IREM: u8 = 112;
+const ISHL: u8 = 120;
+const ISHR: u8 = 122;
+const IUSHR: u8 = 124;
+const IAND: u8 = 126;
+const IOR: u8 = 128;
+const IXOR: u8 = 130;
+const IINC: u8 = 132;
 const IRETURN: u8 = 172;
 const ISTORE: u8 = 54;
 const ISTORE_0: u8 = 59;
@@ -66,12 +99,25 @@ const LLOAD_2: u8 = 32;
 const LLOAD_3: u8 = 33;
 const LNEG: u8 = 117;
 const LADD: u8 = 97;
+const LSUB: u8 = 101;
+const LMUL: u8 = 105;
+const LDIV: u8 = 109;
+const LREM: u8 = 113;
+const LSHL: u8 = 121;
+const LSHR: u8 = 123;
+const LUSHR: u8 = 125;
+const LAND: u8 = 127;
+const LOR: u8 = 129;
+const LXOR: u8 = 131;
+const LCMP: u8 = 148;
 const LRETURN: u8 = 173;
 const LSTORE: u8 = 55;
 const LSTORE_0: u8 = 63;
 const LSTORE_1: u8 = 64;
 const LSTORE_2: u8 = 65;
 const LSTORE_3: u8 = 66;
+const LALOAD: u8 = 47;
+const LASTORE: u8 = 80;
 
 const FCONST_0: u8 = 11;
 const FCONST_1: u8 = 12;
@@ -83,12 +129,20 @@ const FLOAD_2: u8 = 36;
 const FLOAD_3: u8 = 37;
 const FNEG: u8 = 118;
 const FADD: u8 = 98;
+const FSUB: u8 = 102;
+const FMUL: u8 = 106;
+const FDIV: u8 = 110;
+const FREM: u8 = 114;
+const FCMPL: u8 = 149;
+const FCMPG: u8 = 150;
 const FRETURN: u8 = 174;
 const FSTORE: u8 = 56;
 const FSTORE_0: u8 = 67;
 const FSTORE_1: u8 = 68;
 const FSTORE_2: u8 = 69;
 const FSTORE_3: u8 = 70;
+const FALOAD: u8 = 48;
+const FASTORE: u8 = 81;
 
 const DCONST_0: u8 = 14;
 const DCONST_1: u8 = 15;
@@ -99,12 +153,27 @@ const DLOAD_2: u8 = 40;
 const DLOAD_3: u8 = 41;
 const DNEG: u8 = 119;
 const DADD: u8 = 99;
+const DSUB: u8 = 103;
+const DMUL: u8 = 107;
+const DDIV: u8 = 111;
+const DREM: u8 = 115;
+const DCMPL: u8 = 151;
+const DCMPG: u8 = 152;
 const DRETURN: u8 = 175;
 const DSTORE: u8 = 57;
 const DSTORE_0: u8 = 71;
 const DSTORE_1: u8 = 72;
 const DSTORE_2: u8 = 73;
 const DSTORE_3: u8 = 74;
+const DALOAD: u8 = 49;
+const DASTORE: u8 = 82;
+
+const BALOAD: u8 = 51;
+const BASTORE: u8 = 84;
+const CALOAD: u8 = 52;
+const CASTORE: u8 = 85;
+const SALOAD: u8 = 53;
+const SASTORE: u8 = 86;
 
 const IFEQ: u8 = 153;
 const IFNE: u8 = 154;
@@ -122,76 +191,154 @@ const IF_ACMPEQ: u8 = 165;
 const IF_ACMPNE: u8 = 166;
 const IFNULL: u8 = 198;
 const IFNONNULL: u8 = 199;
+const GOTO: u8 = 167;
+const GOTO_W: u8 = 200;
+const TABLESWITCH: u8 = 170;
+const LOOKUPSWITCH: u8 = 171;
+const JSR: u8 = 168;
+const JSR_W: u8 = 201;
+const RET: u8 = 169;
+
+const WIDE: u8 = 196;
+
+const I2L: u8 = 133;
+const I2F: u8 = 134;
+const I2D: u8 = 135;
+const L2I: u8 = 136;
+const L2F: u8 = 137;
+const L2D: u8 = 138;
+const F2I: u8 = 139;
+const F2L: u8 = 140;
+const F2D: u8 = 141;
+const D2I: u8 = 142;
+const D2L: u8 = 143;
+const D2F: u8 = 144;
+const I2B: u8 = 145;
+const I2C: u8 = 146;
+const I2S: u8 = 147;
 
 const NEWARRAY: u8 = 188;
-
+const ANEWARRAY: u8 = 189;
+const MULTIANEWARRAY: u8 = 197;
+const ARRAYLENGTH: u8 = 190;
+const AALOAD: u8 = 50;
+const AASTORE: u8 = 83;
+
+const GETSTATIC: u8 = 178;
+const PUTSTATIC: u8 = 179;
 const GETFIELD: u8 = 180;
 const PUTFIELD: u8 = 181;
 
+const SIPUSH: u8 = 17;
 const LDC: u8 = 18;
+const LDC_W: u8 = 19;
+const LDC2_W: u8 = 20;
 
 const NEW: u8 = 187;
 
 const INVOKESPECIAL: u8 = 183;
 const INVOKESTATIC: u8 = 184;
 const INVOKEVIRTUAL: u8 = 182;
+const INVOKEINTERFACE: u8 = 185;
+const INVOKEDYNAMIC: u8 = 186;
 
-const DUP: u8 = 89;
+const CHECKCAST: u8 = 192;
+const INSTANCEOF: u8 = 193;
 
+const POP: u8 = 87;
+const POP2: u8 = 88;
+const DUP: u8 = 89;
+const DUP_X1: u8 = 90;
+const DUP_X2: u8 = 91;
+const DUP2: u8 = 92;
+const DUP2_X1: u8 = 93;
+const DUP2_X2: u8 = 94;
+const SWAP: u8 = 95;
+
+const ATHROW: u8 = 191;
+const MONITORENTER: u8 = 194;
+const MONITOREXIT: u8 = 195;
+
+const ARETURN: u8 = 176;
 const RETURN: u8 = 177;
 
 pub struct JVM {
     thread: JThread,
-    heap: Rc<RefCell<Heap>>,
-    method_area: Rc<MethodArea>
 }
 
 impl JVM {
     pub fn new() -> Result<Self> {
-        let mut method_area = MethodArea::new();
-        let class = crate::class::load("java/Add.class")?;
-        println!("class: {:?}", class);
-        method_area.classes.insert(class.name.clone(), Rc::new(class));
+        let method_area = MethodArea::new(vec!["java".into(), "rt".into()]);
 
-        let method_area = Rc::new(method_area);
+        let method_area = Rc::new(RefCell::new(method_area));
         let heap = Rc::new(RefCell::new(Heap::new()));
-        let thread = JThread::new(method_area.clone(), heap.clone());
+        let thread = JThread::new(method_area, heap);
 
-        Ok(Self { method_area, thread, heap })
+        Ok(Self { thread })
     }
 
     pub fn run(&mut self, class_name: &str, method_name: &str, args: &[JTypeValue]) -> Result<JTypeValue> {
-        return self.thread.execute_method(class_name, method_name, args);
+        self.thread.execute_method(class_name, method_name, args)
     }
-}
 
+    /// Allocates a java.lang.String, e.g. to pass it as an argument to `run`.
+    pub fn new_string(&mut self, text: &str) -> Result<JTypeValue> {
+        Ok(JTypeValue::Ref(self.thread.new_string(text)?))
+    }
 
-#[derive(Debug)]
-struct MethodArea {
-    classes: HashMap<Rc<str>, Rc<Class>>
-}
+    /// Allocates a java.lang.String[], e.g. to pass the arguments of `main` to `run`.
+    pub fn new_string_array(&mut self, texts: &[&str]) -> Result<JTypeValue> {
+        Ok(JTypeValue::Ref(self.thread.new_string_array(texts)?))
+    }
 
-impl MethodArea {
-    fn new() -> Self {
-        Self { classes: HashMap::new() }
+    /// Reads the text of a java.lang.String, e.g. one returned by `run`.
+    pub fn read_string(&self, string: JTypeValue) -> Result<String> {
+        match string {
+            JTypeValue::Ref(r) if r != 0 => self.thread.read_string(r),
+            v => Err(anyhow!("expected a string reference but found {:?}", v))
+        }
     }
 }
 
 struct JThread {
     stack: Vec<Frame>,
-    method_area: Rc<MethodArea>,
+    method_area: Rc<RefCell<MethodArea>>,
     heap: Rc<RefCell<Heap>>,
+    // Address on the native stack where the running entry point started, see `native_stack_used`
+    native_stack_base: usize,
 }
 
 impl JThread {
-    fn new(method_area: Rc<MethodArea>, heap: Rc<RefCell<Heap>>) -> Self {
-        Self {stack: Vec::new(), method_area, heap}
+    fn new(method_area: Rc<RefCell<MethodArea>>, heap: Rc<RefCell<Heap>>) -> Self {
+        Self {stack: Vec::new(), method_area, heap, native_stack_base: 0}
     }
 
     fn execute_method(&mut self, class_name: &str, method_name: &str, args: &[JTypeValue]) -> Result<JTypeValue> {
         println!("running {}.{} with {:?}", class_name, method_name, args);
+        self.native_stack_base = Self::native_stack_address();
+
+        // An exception raised by the VM which ends the thread is returned together with its Java object
+        match self.execute_entry_point(class_name, method_name, args).map_err(|e| e.downcast::<JavaException>()) {
+            Err(Ok(mut exception)) => {
+                exception.exception = Some(self.new_exception(&exception)?);
+                Err(exception.into())
+            },
+            Err(Err(e)) => Err(e),
+            Ok(value) => Ok(value)
+        }
+    }
 
-        let f = self.build_frame(class_name, method_name, args)?;
+    fn execute_entry_point(&mut self, class_name: &str, method_name: &str, args: &[JTypeValue]) -> Result<JTypeValue> {
+        let class = self.load_class(class_name)?;
+        self.initialize_class(&class)?;
+
+        // Entry points are looked up by name only, the first method with a matching name wins
+        let method = match class.methods.iter().position(|m| m.name.deref() == method_name) {
+            Some(index) => ResolvedMethod { class: class.clone(), index },
+            None => return Err(JavaException::new(NO_SUCH_METHOD_ERROR, format!("{}.{}", class_name, method_name)).into())
+        };
+
+        let f = self.build_frame(&method, args)?;
         self.stack.push(f);
 
         let result = self.execute()?;
@@ -199,23 +346,242 @@ impl JThread {
         Ok(result)
     }
 
-    fn build_frame(&self, class_name: &str, method_name: &str, args: &[JTypeValue]) -> Result<Frame> {
-        let class = match self.method_area.classes.get(class_name) {
-            Some(c) => c,
-            None => return Err(anyhow!("no such class error"))
+    fn load_class(&self, class_name: &str) -> Result<Rc<RuntimeClass>> {
+        self.method_area.borrow_mut().load_class(class_name)
+    }
+
+    // Initialization, see https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-5.html#jvms-5.5
+    fn initialize_class(&mut self, class: &Rc<RuntimeClass>) -> Result<()> {
+        match class.state.get() {
+            ClassState::Loaded => { },
+            ClassState::Erroneous => {
+                let message = format!("Could not initialize class {}", class.name.replace('/', "."));
+                return Err(JavaException::new(NO_CLASS_DEF_FOUND_ERROR, message).into());
+            },
+            // A class being initialized can only be seen here by the initializing thread itself (e.g. <clinit>
+            // touching its own statics), in which case it is treated as initialized
+            ClassState::BeingInitialized | ClassState::Initialized => return Ok(())
+        }
+
+        class.state.set(ClassState::BeingInitialized);
+
+        // Constant fields are set before anything else runs, for Strings this needs interned objects
+        for (index, text) in class.string_constants()? {
+            let string_ref = self.intern_string(&text)?;
+            class.set_static(index, JTypeValue::Ref(string_ref));
+        }
+
+        // A super class failing to initialize leaves the class erroneous too, with the same exception
+        if let Some(super_class) = &class.super_class {
+            if let Err(e) = self.initialize_class(super_class) {
+                class.state.set(ClassState::Erroneous);
+                return Err(e);
+            }
+        }
+
+        if let Some(index) = class.find_method("<clinit>", "()V") {
+            let clinit = ResolvedMethod { class: class.clone(), index };
+            let clinit_frame = self.build_frame(&clinit, &[])?;
+            self.stack.push(clinit_frame);
+
+            if let Err(e) = self.execute() {
+                class.state.set(ClassState::Erroneous);
+                return Err(self.initializer_error(e)?);
+            }
+        }
+
+        class.state.set(ClassState::Initialized);
+        Ok(())
+    }
+
+    // Exceptions thrown by a class initializer are wrapped into ExceptionInInitializerError, errors are passed on
+    fn initializer_error(&mut self, err: anyhow::Error) -> Result<anyhow::Error> {
+        if let Some(thrown) = err.downcast_ref::<ThrownException>() {
+            if self.is_error(&thrown.class_name)? {
+                return Ok(err);
+            }
+
+            return Ok(self.wrap_thrown(EXCEPTION_IN_INITIALIZER_ERROR, "", thrown)?.into());
+        }
+
+        match err.downcast::<JavaException>() {
+            Ok(exception) if !self.is_error(exception.class_name)? => {
+                Ok(JavaException::with_cause(EXCEPTION_IN_INITIALIZER_ERROR, String::new(), exception).into())
+            },
+            Ok(exception) => Ok(exception.into()),
+            Err(e) => Ok(e)
+        }
+    }
+
+    fn resolve_static_field(&self, field_ref: &FieldRef) -> Result<(Rc<RuntimeClass>, usize)> {
+        let class = self.load_class(&field_ref.class_name)?;
+
+        let (declaring_class, index) = match class.resolve_field(&field_ref.field_name, &field_ref.field_desc) {
+            Some(f) => f,
+            None => return Err(JavaException::new(NO_SUCH_FIELD_ERROR, field_ref.field_name.to_string()).into())
+        };
+
+        if !declaring_class.fields[index].is_static() {
+            let message = format!("Expected static field {}.{}", field_ref.class_name.replace('/', "."), field_ref.field_name);
+            return Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into());
+        }
+
+        Ok((declaring_class, index))
+    }
+
+    fn resolve_instance_field(&self, field_ref: &FieldRef) -> Result<usize> {
+        let class = self.load_class(&field_ref.class_name)?;
+
+        let (declaring_class, index) = match class.resolve_field(&field_ref.field_name, &field_ref.field_desc) {
+            Some(f) => f,
+            None => return Err(JavaException::new(NO_SUCH_FIELD_ERROR, field_ref.field_name.to_string()).into())
+        };
+
+        match declaring_class.field_offset(index) {
+            Some(offset) => Ok(offset),
+            None => {
+                let message = format!("Expected non-static field {}.{}", field_ref.class_name.replace('/', "."), field_ref.field_name);
+                Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into())
+            }
+        }
+    }
+
+    /// Resolves a method referenced by the current class and checks it can be invoked with the given
+    /// invocation kind, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.invokestatic
+    fn resolve_method(&self, method_ref: &StaticMethod, is_static: bool) -> Result<ResolvedMethod> {
+        // Methods of array classes are those of java/lang/Object, e.g. int[].clone()
+        let class = match method_ref.class_name.starts_with('[') {
+            true => self.load_class(OBJECT_CLASS)?,
+            false => self.load_class(&method_ref.class_name)?
         };
 
-        let method = match class.methods.iter().find(|&m|  m.name.deref() == method_name) {
-            Some(m) => m,
-            None => return Err(anyhow!("no such method"))
+        let method = if method_ref.is_interface {
+            class.resolve_interface_method(&method_ref.method_name, &method_ref.method_desc)?
+        } else {
+            class.resolve_method(&method_ref.method_name, &method_ref.method_desc)?
         };
 
-        let code = match method.attributes.iter().find(|&a| a.name.deref() == "Code") {
+        if method.method().is_static() != is_static {
+            let message = format!("Expecting {} method {}", if is_static { "static" } else { "non-static" }, method.signature());
+            return Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into());
+        }
+
+        Ok(method)
+    }
+
+    // Turns the outcome of method selection into the method to invoke or the error to raise
+    fn selected_method(selected: Selected, resolved: &ResolvedMethod, receiver_class: &RuntimeClass) -> Result<ResolvedMethod> {
+        match selected {
+            Selected::Method(m) => Ok(m),
+            Selected::Missing => {
+                let message = format!("Receiver class {} does not define or inherit an implementation of the resolved method {} of {} {}.",
+                                      receiver_class.name.replace('/', "."),
+                                      resolved.signature().replacen('\'', "'abstract ", 1),
+                                      if resolved.class.is_interface() { "interface" } else { "abstract class" },
+                                      resolved.class.name.replace('/', "."));
+                Err(JavaException::new(ABSTRACT_METHOD_ERROR, message).into())
+            },
+            Selected::Conflict(methods) => {
+                let names: Vec<String> = methods.iter()
+                    .map(|m| format!("{}.{}", m.class.name.replace('/', "."), m.method().name))
+                    .collect();
+                Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, format!("Conflicting default methods: {}", names.join(" "))).into())
+            }
+        }
+    }
+
+    fn invoke(&mut self, method: &ResolvedMethod, args: &[JTypeValue]) -> Result<JTypeValue> {
+        if method.method().is_native() {
+            return self.invoke_native(method, args);
+        }
+
+        let invoked_method_frame = self.build_frame(method, args)?;
+        self.stack.push(invoked_method_frame);
+
+        self.execute()
+    }
+
+    // Invokes a method for the invoke instruction of the top frame, which is `length` bytes long. Native methods run
+    // right away, anything else gets a frame the interpreter continues with, see `return_value`
+    fn call(&mut self, method: &ResolvedMethod, args: &[JTypeValue], length: usize) -> Result<()> {
+        if method.method().is_native() {
+            let result = self.invoke_native(method, args)?;
+
+            let frame = self.top_frame_mut()?;
+            frame.push_stack(result);
+            frame.inc_ip(length);
+            return Ok(());
+        }
+
+        let invoked_method_frame = self.build_frame(method, args)?;
+        self.stack.push(invoked_method_frame);
+        Ok(())
+    }
+
+    // Passes the value returned by a popped frame to the invoke instruction of its caller. When the popped frame is the
+    // one `execute` started with, the value is returned instead
+    fn return_value(&mut self, depth: usize, value: JTypeValue) -> Result<Option<JTypeValue>> {
+        if self.stack.len() < depth {
+            return Ok(Some(value));
+        }
+
+        let frame = self.top_frame_mut()?;
+        let length = if frame.code[frame.ip] == INVOKEINTERFACE { 5 } else { 3 };
+        frame.push_stack(value);
+        frame.inc_ip(length);
+        Ok(None)
+    }
+
+    // Private methods are never overridden, everything else is selected from the receiver's class
+    fn select_virtual(&self, resolved: ResolvedMethod, obj_ref: usize) -> Result<ResolvedMethod> {
+        if resolved.method().is_private() {
+            return Ok(resolved);
+        }
+
+        let receiver_class = self.receiver_class(obj_ref)?;
+        let selected = receiver_class.select_virtual(&resolved);
+        Self::selected_method(selected, &resolved, &receiver_class)
+    }
+
+    // The class methods are selected from, arrays only have the methods of java/lang/Object
+    fn receiver_class(&self, obj_ref: usize) -> Result<Rc<RuntimeClass>> {
+        let heap = RefCell::borrow(&self.heap);
+
+        match heap.objects.get(&obj_ref) {
+            Some(object) => Ok(object.class.clone()),
+            None => {
+                heap.get_arr(obj_ref)?;
+                self.load_class(OBJECT_CLASS)
+            }
+        }
+    }
+
+    /// Calls an instance method of the object the way invokevirtual does, for calls made by the VM itself.
+    fn invoke_virtual(&mut self, obj_ref: usize, method_name: &str, method_desc: &str) -> Result<JTypeValue> {
+        let receiver_class = self.receiver_class(obj_ref)?;
+        let resolved = receiver_class.resolve_method(method_name, method_desc)?;
+        let method = self.select_virtual(resolved, obj_ref)?;
+
+        self.invoke(&method, &[JTypeValue::Ref(obj_ref)])
+    }
+
+    fn build_frame(&self, resolved: &ResolvedMethod, args: &[JTypeValue]) -> Result<Frame> {
+        let method = resolved.method();
+
+        if self.stack.len() >= MAX_STACK_DEPTH {
+            return Err(JavaException::new(STACK_OVERFLOW_ERROR, String::new()).into());
+        }
+
+        if method.is_abstract() {
+            return Err(JavaException::new(ABSTRACT_METHOD_ERROR, resolved.signature()).into());
+        }
+
+        let code = match method.attribute("Code") {
             Some(c) => c,
-            None => return Err(anyhow!("'code' attribute not found!"))
+            None => return Err(anyhow!("'code' attribute not found for {}!", resolved.signature()))
         };
 
-        let _max_locals = u16::from_be_bytes([code.data[2],code.data[3]]) as usize;
+        let max_locals = u16::from_be_bytes([code.data[2],code.data[3]]) as usize;
 
         let mut locals = Vec::<JTypeValue>::new();
         for a in args.iter() {
@@ -226,29 +592,155 @@ impl JThread {
             }
         }
 
-        let frame = Frame::new(class.clone(),  code.data[8..].to_vec(), locals);
+        if locals.len() < max_locals {
+            locals.resize(max_locals, JTypeValue::Empty);
+        }
+
+        let frame = Frame::new(resolved.class.clone(), resolved.index, code.data[8..].to_vec(), locals);
         Ok(frame)
     }
 
-    fn top_frame(&self) -> &Frame {
-        match self.stack.last() {
-            Some(f) => f,
-            None => panic!("tried to get top frame, but there is nothing!")
+    // Loads a constant pushed by ldc, ldc_w or ldc2_w, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.1
+    // Constants needing resolution are resolved once, later loads give the same object or fail with the same linkage
+    // error, see https://docs.oracle.com/javase/specs/jvms/se11/html/jvms-5.html#jvms-5.4.3
+    fn load_constant(&mut self, class: &Rc<RuntimeClass>, index: usize) -> Result<JTypeValue> {
+        if let Some(resolved) = class.resolved_constant(index) {
+            return resolved;
+        }
+
+        let resolved = match class.const_pool.resolve(index)? {
+            Const::Integer(x) => return Ok(JTypeValue::Int(*x)),
+            Const::Float(x) => return Ok(JTypeValue::Float(*x)),
+            Const::Long(x) => return Ok(JTypeValue::Long(*x)),
+            Const::Double(x) => return Ok(JTypeValue::Double(*x)),
+            Const::StringIndex(_) => {
+                let text = class.const_pool.resolve_string(index)?;
+                self.intern_string(&text).map(JTypeValue::Ref)
+            },
+            Const::ClassIndex(_) => {
+                let class_name = class.const_pool.resolve_class_name(index)?;
+                self.class_mirror(&class_name).map(JTypeValue::Ref)
+            },
+            Const::MethodHandle(_, _) => {
+                let handle = class.const_pool.resolve_method_handle(index)?;
+                self.new_method_handle(&handle)
+            },
+            Const::MethodType(_) => {
+                let desc = class.const_pool.resolve_method_type(index)?;
+                self.new_method_type(&desc)
+            },
+            Const::Dynamic(_, _) => self.resolve_dynamic_constant(class, index),
+            c => return Err(anyhow!("ldc of {:?} is not supported", c))
+        };
+
+        match &resolved {
+            Ok(value) => class.set_resolved_constant(index, Ok(*value)),
+            Err(e) => if let Some(exception) = ResolutionError::of(e) {
+                if self.is_assignable(exception.class_name(), LINKAGE_ERROR)? {
+                    class.set_resolved_constant(index, Err(exception));
+                }
+            }
         }
+
+        resolved
     }
 
-    fn top_frame_mut(&mut self) -> &mut Frame {
+    fn top_frame_mut(&mut self) -> Result<&mut Frame> {
         match self.stack.last_mut() {
-            Some(f) => f,
-            None => panic!("tried to get top frame, but there is nothing")
+            Some(f) => Ok(f),
+            None => Err(anyhow!("tried to get top frame, but there is nothing"))
         }
     }
 
+    // Runs the top frame until it returns, together with the frames of the methods it invokes. An exception goes to the
+    // first matching handler of the top frame's method, without one the frame is popped and the exception passed to
+    // the frame below, until the frame execute started with is popped as well
     fn execute(&mut self) -> Result<JTypeValue> {
+        let depth = self.stack.len();
+
+        if self.native_stack_used() > MAX_NATIVE_STACK {
+            self.stack.truncate(depth - 1);
+            return Err(JavaException::new(STACK_OVERFLOW_ERROR, String::new()).into());
+        }
+
+        loop {
+            let err = match self.interpret(depth) {
+                Ok(value) => return Ok(value),
+                Err(e) => e
+            };
+
+            // Without a handler the frames are gone already, anything left by an internal error is dropped too
+            let (handler_pc, exception) = match self.unwind(depth, err) {
+                Ok(handler) => handler,
+                Err(e) => {
+                    self.stack.truncate(depth - 1);
+                    return Err(e);
+                }
+            };
+
+            let frame = self.top_frame_mut()?;
+            frame.operand_stack.clear();
+            frame.push_stack(exception);
+            frame.ip = handler_pc;
+        }
+    }
+
+    // How much the native stack grew since the entry point started, it grows downwards on every supported platform
+    fn native_stack_used(&self) -> usize {
+        self.native_stack_base.saturating_sub(Self::native_stack_address())
+    }
+
+    #[inline(never)]
+    fn native_stack_address() -> usize {
+        let marker = 0u8;
+        std::hint::black_box(&marker) as *const u8 as usize
+    }
+
+    // Pops frames until one has a handler for the exception, returning the handler and the exception object. Without
+    // one the exception is returned once the frame execute started with is popped as well
+    fn unwind(&mut self, depth: usize, mut err: anyhow::Error) -> Result<(usize, JTypeValue)> {
+        loop {
+            match self.handler(&err) {
+                Ok(Some(handler)) => return Ok(handler),
+                Ok(None) => { },
+                // A catch type failing to resolve throws from the handler instead, which is searched from there on
+                Err(e) if ResolutionError::of(&e).is_some() => {
+                    err = e;
+                    continue;
+                },
+                Err(e) => return Err(e)
+            }
+
+            self.stack.pop();
+
+            if self.stack.len() < depth {
+                return Err(err);
+            }
+        }
+    }
+
+    // The handler in the top frame's method for an exception, internal errors are never handled
+    fn handler(&mut self, err: &anyhow::Error) -> Result<Option<(usize, JTypeValue)>> {
+        if let Some(thrown) = err.downcast_ref::<ThrownException>() {
+            return Ok(self.find_handler(&thrown.class_name)?.map(|pc| (pc, thrown.exception)));
+        }
+
+        // Exceptions raised by the VM only become Java objects once there is a handler catching them or they end the
+        // thread
+        if let Some(exception) = err.downcast_ref::<JavaException>() {
+            if let Some(pc) = self.find_handler(exception.class_name)? {
+                return Ok(Some((pc, self.new_exception(exception)?)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn interpret(&mut self, depth: usize) -> Result<JTypeValue> {
         loop {
-            let frame = self.top_frame_mut();
+            let frame = self.top_frame_mut()?;
 
-            let op = frame.code[frame.ip as usize];
+            let op = frame.code[frame.ip];
             println!("OP: {}, stack: {:?}", op, frame.operand_stack);
 
             match op {
@@ -340,15 +832,105 @@ impl JThread {
                 },
                 INEG | LNEG | FNEG | DNEG => { // ineg
                     let var = frame.pop_stack()?;
-                    frame.push_stack(-var);
+                    frame.push_stack((-var)?);
                     frame.inc_ip(1);
                 }
                 IADD | LADD | FADD | DADD => { // iadd
                     let a = frame.pop_stack()?;
                     let b = frame.pop_stack()?;
-                    frame.push_stack(a + b);
+                    frame.push_stack((a + b)?);
+                    frame.inc_ip(1);
+                },
+                ISUB | IMUL | IDIV | IREM | ISHL | ISHR | IUSHR | IAND | IOR | IXOR |
+                LSUB | LMUL | LDIV | LREM | LSHL | LSHR | LUSHR | LAND | LOR | LXOR |
+                FSUB | FMUL | FDIV | FREM | DSUB | DMUL | DDIV | DREM => {
+                    // for long shifts value2 is the int shift distance
+                    let value2 = frame.pop_stack()?;
+                    let value1 = frame.pop_stack()?;
+
+                    if matches!(op, IDIV | IREM | LDIV | LREM) && value2.is_zero_divisor() {
+                        return Err(JavaException::new(ARITHMETIC_EXCEPTION, "/ by zero".to_string()).into());
+                    }
+
+                    let result = match op {
+                        ISUB | LSUB | FSUB | DSUB => (value1 - value2)?,
+                        IMUL | LMUL | FMUL | DMUL => (value1 * value2)?,
+                        IDIV | LDIV | FDIV | DDIV => (value1 / value2)?,
+                        IREM | LREM | FREM | DREM => (value1 % value2)?,
+                        ISHL | LSHL => (value1 << value2)?,
+                        ISHR | LSHR => (value1 >> value2)?,
+                        IUSHR | LUSHR => value1.ushr(value2)?,
+                        IAND | LAND => (value1 & value2)?,
+                        IOR | LOR => (value1 | value2)?,
+                        IXOR | LXOR => (value1 ^ value2)?,
+                        _ => return Err(anyhow!("impossible - op {} is outside the expected range", op))
+                    };
+
+                    frame.push_stack(result);
                     frame.inc_ip(1);
                 },
+                LCMP => {
+                    let value2 = frame.pop_long()?;
+                    let value1 = frame.pop_long()?;
+
+                    frame.push_stack(JTypeValue::Int(value1.cmp(&value2) as i32));
+                    frame.inc_ip(1);
+                },
+                FCMPL | FCMPG | DCMPL | DCMPG => {
+                    let value2 = frame.pop_stack()?;
+                    let value1 = frame.pop_stack()?;
+
+                    let ordering = match (value1, value2) {
+                        (JTypeValue::Float(a), JTypeValue::Float(b)) => a.partial_cmp(&b),
+                        (JTypeValue::Double(a), JTypeValue::Double(b)) => a.partial_cmp(&b),
+                        (a, b) => return Err(anyhow!("cannot compare {:?} with {:?}", a, b))
+                    };
+
+                    // Comparisons involving NaN are unordered, the g variants then push 1 and the l variants -1
+                    let result = match ordering {
+                        Some(o) => o as i32,
+                        None if matches!(op, FCMPG | DCMPG) => 1,
+                        None => -1
+                    };
+
+                    frame.push_stack(JTypeValue::Int(result));
+                    frame.inc_ip(1);
+                },
+                // Rust's casts follow the same rules as Java's conversions: floating-point values are rounded towards
+                // zero and saturate with NaN giving 0, narrowing integers drops the high bits, see
+                // https://docs.oracle.com/javase/specs/jls/se8/html/jls-5.html#jls-5.1.3
+                I2L..=I2S => {
+                    let value = frame.pop_stack()?;
+
+                    let converted = match (op, value) {
+                        (I2L, JTypeValue::Int(i)) => JTypeValue::Long(i as i64),
+                        (I2F, JTypeValue::Int(i)) => JTypeValue::Float(i as f32),
+                        (I2D, JTypeValue::Int(i)) => JTypeValue::Double(i as f64),
+                        (L2I, JTypeValue::Long(l)) => JTypeValue::Int(l as i32),
+                        (L2F, JTypeValue::Long(l)) => JTypeValue::Float(l as f32),
+                        (L2D, JTypeValue::Long(l)) => JTypeValue::Double(l as f64),
+                        (F2I, JTypeValue::Float(f)) => JTypeValue::Int(f as i32),
+                        (F2L, JTypeValue::Float(f)) => JTypeValue::Long(f as i64),
+                        (F2D, JTypeValue::Float(f)) => JTypeValue::Double(f as f64),
+                        (D2I, JTypeValue::Double(d)) => JTypeValue::Int(d as i32),
+                        (D2L, JTypeValue::Double(d)) => JTypeValue::Long(d as i64),
+                        (D2F, JTypeValue::Double(d)) => JTypeValue::Float(d as f32),
+                        (I2B, JTypeValue::Int(i)) => JTypeValue::Int(i as i8 as i32),
+                        (I2C, JTypeValue::Int(i)) => JTypeValue::Int(i as u16 as i32),
+                        (I2S, JTypeValue::Int(i)) => JTypeValue::Int(i as i16 as i32),
+                        (_, v) => return Err(anyhow!("cannot convert {:?} with opcode {}", v, op))
+                    };
+
+                    frame.push_stack(converted);
+                    frame.inc_ip(1);
+                },
+                IINC => {
+                    let index = frame.code[frame.ip + 1] as usize;
+                    let increment = frame.code[frame.ip + 2] as i8;
+
+                    frame.locals[index] = (frame.locals[index] + JTypeValue::Int(increment as i32))?;
+                    frame.inc_ip(3);
+                },
                 ASTORE | ISTORE | LSTORE | FSTORE | DSTORE => {
                     let v = frame.pop_stack()?;
                     let index = frame.code[frame.ip + 1];
@@ -375,48 +957,69 @@ impl JThread {
                     frame.locals[3] = v;
                     frame.inc_ip(1)
                 },
-                IASTORE => {
+                IASTORE | LASTORE | FASTORE | DASTORE | BASTORE | CASTORE | SASTORE => {
                     let value = frame.pop_stack()?;
-                    let index = frame.pop_int();
-                    let arr_ref = frame.pop_ref();
+                    let index = frame.pop_int()?;
+                    let arr_ref = frame.pop_ref()?;
+
+                    let index = self.array_index(op, arr_ref, index)?;
+                    self.heap.borrow_mut().get_arr_mut(arr_ref)?.set(index, value)?;
 
-                    {
-                        let mut heap = self.heap.borrow_mut();
-                        let arr = heap.get_arr_mut(arr_ref);
-                        arr.set(index as usize, value);
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.inc_ip(1);
+                },
+
+                AASTORE => {
+                    let value_ref = frame.pop_ref()?;
+                    let index = frame.pop_int()?;
+                    let arr_ref = frame.pop_ref()?;
+
+                    let index = self.array_index(op, arr_ref, index)?;
+
+                    if value_ref != 0 && !self.can_store_reference(arr_ref, value_ref)? {
+                        let value_class = RefCell::borrow(&self.heap).class_name(value_ref)?;
+                        return Err(JavaException::new(ARRAY_STORE_EXCEPTION, value_class.replace('/', ".")).into());
                     }
 
-                    let frame_mut = self.top_frame_mut();
+                    self.heap.borrow_mut().get_arr_mut(arr_ref)?.set(index, JTypeValue::Ref(value_ref))?;
+
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.inc_ip(1);
                 },
 
-                IALOAD => {
-                    let index = frame.pop_int();
-                    let array_ref = frame.pop_ref();
+                IALOAD | LALOAD | FALOAD | DALOAD | BALOAD | CALOAD | SALOAD | AALOAD => {
+                    let index = frame.pop_int()?;
+                    let array_ref = frame.pop_ref()?;
 
-                    let val = {
-                        let heap = RefCell::borrow(&self.heap);
-                        let arr = heap.get_arr(array_ref);
-                        arr.get_int(index as usize)
-                    };
+                    let index = self.array_index(op, array_ref, index)?;
+                    let val = RefCell::borrow(&self.heap).get_arr(array_ref)?.get(index);
 
-                    let frame_mut = self.top_frame_mut();
-                    frame_mut.push_stack(JTypeValue::Int(val));
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(val);
                     frame_mut.inc_ip(1);
-                }
+                },
 
-                LDC => { // TODO implement other LDC e.g. LDC_2W
-                    let index = frame.code[frame.ip + 1];
+                ARRAYLENGTH => {
+                    let array_ref = frame.pop_ref()?;
+                    let length = self.array_length(op, array_ref)?;
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(JTypeValue::Int(length as i32));
+                    frame_mut.inc_ip(1);
+                },
 
-                    let c = frame.class.const_pool.resolve(index as usize)?;
+                LDC | LDC_W | LDC2_W => {
+                    let (index, length) = match op {
+                        LDC => (frame.code[frame.ip + 1] as usize, 2),
+                        _ => (u16::from_be_bytes([frame.code[frame.ip + 1], frame.code[frame.ip + 2]]) as usize, 3)
+                    };
+                    let class = frame.class.clone();
 
-                    match c {
-                        Const::Integer(x) => frame.operand_stack.push(JTypeValue::Int(*x)),
-                        Const::Float(x) => frame.operand_stack.push(JTypeValue::Float(*x)),
-                        _ => panic!("not supported") // TODO implement support for references and String literals
-                    }
+                    let value = self.load_constant(&class, index)?;
 
-                    frame.inc_ip(2);
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(value);
+                    frame_mut.inc_ip(length);
                 },
 
                 INVOKESTATIC => { // invokestatic
@@ -428,41 +1031,84 @@ impl JThread {
 
                     let nargs = Self::get_nargs(&static_method.method_desc);
 
-                    // let frame_mut = self.top_frame_mut();
-                    let locals = Self::pop_operand_stack_to_locals(frame, nargs);
+                    // let frame_mut = self.top_frame_mut()?;
+                    let locals = Self::pop_operand_stack_to_locals(frame, nargs)?;
+
+                    let method = self.resolve_method(&static_method, true)?;
+                    self.initialize_class(&method.class)?;
 
-                    let invoked_method_frame = self.build_frame(&static_method.class_name, &static_method.method_name, &locals)?;
+                    self.call(&method, &locals, 3)?;
+                }
 
-                    self.stack.push(invoked_method_frame);
+                IRETURN | LRETURN | FRETURN | DRETURN | ARETURN | RETURN => {
+                    let value = if op == RETURN { JTypeValue::Empty } else { frame.pop_stack()? };
+                    self.stack.pop();
 
-                    // Currently handled recursively, maybe it could be done iteratively?
-                    let result = self.execute()?;
+                    if let Some(value) = self.return_value(depth, value)? {
+                        return Ok(value);
+                    }
+                },
 
-                    let frame_mut = self.top_frame_mut();
-                    frame_mut.push_stack(result);
-                    frame_mut.inc_ip(3);
-                }
+                ATHROW => {
+                    let exception_ref = frame.pop_ref()?;
 
-                IRETURN | LRETURN | FRETURN | DRETURN => {
-                    let mut frame =  match self.stack.pop() {
-                        Some(f) => f,
-                        None => panic!("no frame to pop")
-                    };
-                    return frame.pop_stack();
+                    if exception_ref == 0 {
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, "Cannot throw exception".to_string()).into());
+                    }
+
+                    return Err(self.throw(exception_ref)?.into());
                 },
 
-                RETURN => {
-                    self.stack.pop();
-                    return Ok(JTypeValue::Empty);
-                }
+                // There is a single thread, so entering a monitor never blocks
+                MONITORENTER | MONITOREXIT => {
+                    let obj_ref = frame.pop_ref()?;
 
-                DUP => {
-                    let top_value = match frame.operand_stack.last() {
-                        Some(v) => *v,
-                        None => panic!("no value to dup!")
+                    if obj_ref == 0 {
+                        let action = if op == MONITORENTER { "enter" } else { "exit" };
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, format!("Cannot {} synchronized block", action)).into());
+                    }
+
+                    if op == MONITORENTER {
+                        self.heap.borrow_mut().enter_monitor(obj_ref);
+                    } else if !self.heap.borrow_mut().exit_monitor(obj_ref) {
+                        return Err(JavaException::new(ILLEGAL_MONITOR_STATE_EXCEPTION, String::new()).into());
+                    }
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.inc_ip(1);
+                },
+
+                // The stack instructions count in words, in which longs and doubles take two, see
+                // https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-2.html#jvms-2.11.1
+                POP | POP2 => {
+                    frame.pop_words(if op == POP { 1 } else { 2 })?;
+                    frame.inc_ip(1);
+                },
+
+                DUP..=DUP2_X2 => {
+                    let (words, below) = match op {
+                        DUP => (1, 0),
+                        DUP_X1 => (1, 1),
+                        DUP_X2 => (1, 2),
+                        DUP2 => (2, 0),
+                        DUP2_X1 => (2, 1),
+                        _ => (2, 2)
                     };
 
-                    frame.push_stack(top_value);
+                    frame.dup_words(words, below)?;
+                    frame.inc_ip(1);
+                },
+
+                SWAP => {
+                    let value1 = frame.pop_stack()?;
+                    let value2 = frame.pop_stack()?;
+
+                    if value1.category() != 1 || value2.category() != 1 {
+                        return Err(anyhow!("swap of {:?} and {:?}, both values must be of category 1", value1, value2));
+                    }
+
+                    frame.push_stack(value1);
+                    frame.push_stack(value2);
                     frame.inc_ip(1);
                 },
 
@@ -472,23 +1118,36 @@ impl JThread {
                     let class_index_byte2 = frame.code[frame.ip + 2];
                     let class_index = u16::from_be_bytes([class_index_byte1, class_index_byte2]);
 
-                    // build an object for the class
-                    let obj = Object::new(frame.class.clone());
+                    let class_name = frame.class.const_pool.resolve_class_name(class_index as usize)?;
+                    let class = self.load_class(&class_name)?;
+
+                    if class.is_interface() || class.is_abstract() {
+                        return Err(JavaException::new(INSTANTIATION_ERROR, class_name.replace('/', ".")).into());
+                    }
+
+                    self.initialize_class(&class)?;
+
+                    let obj = Object::new(class);
                     let obj_ref = self.heap.borrow_mut().allocate_obj(obj);
 
-                    let frame_mut = self.top_frame_mut();
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.push_stack(JTypeValue::Ref(obj_ref));
                     frame_mut.inc_ip(3);
                 },
 
                 BIPUSH => {
-                    let byte = frame.code[frame.ip + 1];
+                    let byte = frame.code[frame.ip + 1] as i8;
                     frame.push_stack(JTypeValue::Int(byte as i32));
                     frame.inc_ip(2);
                 },
 
-                // TODO implement INVOKEVIRTUAL properly
-                INVOKESPECIAL | INVOKEVIRTUAL => {
+                SIPUSH => {
+                    let short = frame.read_i16(frame.ip + 1);
+                    frame.push_stack(JTypeValue::Int(short as i32));
+                    frame.inc_ip(3);
+                },
+
+                INVOKESPECIAL => {
                     let method_index_byte1 = frame.code[frame.ip + 1];
                     let method_index_byte2 = frame.code[frame.ip + 2];
                     let method_index = u16::from_be_bytes([method_index_byte1, method_index_byte2]);
@@ -497,23 +1156,169 @@ impl JThread {
 
                     // nargs + 1 because we also need to pass instance object reference
                     let nargs = Self::get_nargs(&static_method.method_desc) + 1;
-                    let locals = Self::pop_operand_stack_to_locals(frame, nargs);
-                    print!("locals: {:?}", locals);
+                    let locals = Self::pop_operand_stack_to_locals(frame, nargs)?;
+                    let current_class = frame.class.clone();
 
-                    // TODO remove this hack once java/lang/Object can be properly loaded!
-                    if static_method.class_name.deref() == "java/lang/Object" {
-                        frame.inc_ip(3);
-                        continue;
+                    let resolved = self.resolve_method(&static_method, false)?;
+
+                    if resolved.method().name.deref() == "<init>" && resolved.class.name != static_method.class_name {
+                        return Err(JavaException::new(NO_SUCH_METHOD_ERROR, resolved.signature()).into());
+                    }
+
+                    if locals[0] == NULL_REF {
+                        let message = format!("Cannot invoke \"{}\"", resolved.display_name());
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into());
+                    }
+
+                    // super.method() calls start the lookup in the direct super class of the current class, anything
+                    // else (constructors, private methods, Interface.super.method() calls) in the referenced class
+                    let referenced_class = self.load_class(&static_method.class_name)?;
+                    let lookup_class = match &current_class.super_class {
+                        Some(super_class) if resolved.method().name.deref() != "<init>"
+                            && !referenced_class.is_interface()
+                            && referenced_class.name != current_class.name
+                            && current_class.is_subtype_of(&referenced_class.name) => super_class.clone(),
+                        _ => referenced_class
+                    };
+
+                    let selected = lookup_class.select_special(&static_method.method_name, &static_method.method_desc);
+                    let method = Self::selected_method(selected, &resolved, &lookup_class)?;
+
+                    self.call(&method, &locals, 3)?;
+                },
+
+                INVOKEVIRTUAL => {
+                    let method_index_byte1 = frame.code[frame.ip + 1];
+                    let method_index_byte2 = frame.code[frame.ip + 2];
+                    let method_index = u16::from_be_bytes([method_index_byte1, method_index_byte2]);
+
+                    let static_method = frame.class.const_pool.resolve_static_method(method_index as usize)?;
+
+                    // nargs + 1 because we also need to pass instance object reference
+                    let nargs = Self::get_nargs(&static_method.method_desc) + 1;
+                    let locals = Self::pop_operand_stack_to_locals(frame, nargs)?;
+
+                    let resolved = self.resolve_method(&static_method, false)?;
+
+                    let obj_ref = match locals[0] {
+                        JTypeValue::Ref(r) => r,
+                        v => return Err(anyhow!("INVOKEVIRTUAL called on {:?}", v))
+                    };
+
+                    if obj_ref == 0 {
+                        let message = format!("Cannot invoke \"{}\"", resolved.display_name());
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into());
+                    }
+
+                    if resolved.is_signature_polymorphic() {
+                        let result = self.invoke_polymorphic(&resolved, &static_method.method_desc, &locals)?;
+
+                        let frame_mut = self.top_frame_mut()?;
+                        frame_mut.push_stack(result);
+                        frame_mut.inc_ip(3);
+                    } else {
+                        let method = self.select_virtual(resolved, obj_ref)?;
+                        self.call(&method, &locals, 3)?;
+                    }
+                },
+
+                INVOKEINTERFACE => {
+                    let method_index_byte1 = frame.code[frame.ip + 1];
+                    let method_index_byte2 = frame.code[frame.ip + 2];
+                    let method_index = u16::from_be_bytes([method_index_byte1, method_index_byte2]);
+
+                    let interface_method = frame.class.const_pool.resolve_static_method(method_index as usize)?;
+
+                    // nargs + 1 because we also need to pass instance object reference
+                    let nargs = Self::get_nargs(&interface_method.method_desc) + 1;
+                    let locals = Self::pop_operand_stack_to_locals(frame, nargs)?;
+
+                    if !interface_method.is_interface {
+                        return Err(anyhow!("INVOKEINTERFACE called with a Methodref"));
                     }
 
-                    let invoked_method_frame = self.build_frame(&static_method.class_name, &static_method.method_name, &locals)?;
+                    let resolved = self.resolve_method(&interface_method, false)?;
+
+                    let obj_ref = match locals[0] {
+                        JTypeValue::Ref(r) => r,
+                        v => return Err(anyhow!("INVOKEINTERFACE called on {:?}", v))
+                    };
+
+                    if obj_ref == 0 {
+                        let message = format!("Cannot invoke \"{}\"", resolved.display_name());
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into());
+                    }
+
+                    let object_class = RefCell::borrow(&self.heap).class_name(obj_ref)?;
+
+                    if !self.is_assignable(&object_class, &interface_method.class_name)? {
+                        let message = format!("Class {} does not implement the requested interface {}",
+                                              object_class.replace('/', "."), interface_method.class_name.replace('/', "."));
+                        return Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into());
+                    }
+
+                    let method = if resolved.method().is_private() {
+                        resolved
+                    } else {
+                        // java/lang/Object methods resolved through an interface are selected through the vtable
+                        let receiver_class = self.receiver_class(obj_ref)?;
+                        let selected = receiver_class.select_virtual(&resolved);
+                        Self::selected_method(selected, &resolved, &receiver_class)?
+                    };
+
+                    self.call(&method, &locals, 5)?;
+                },
+
+                INVOKEDYNAMIC => {
+                    let index_byte1 = frame.code[frame.ip + 1];
+                    let index_byte2 = frame.code[frame.ip + 2];
+                    let index = u16::from_be_bytes([index_byte1, index_byte2]) as usize;
+
+                    let (_, _, desc) = frame.class.const_pool.resolve_invoke_dynamic(index)?;
+                    let nargs = Self::get_nargs(&desc);
+                    let args = Self::pop_operand_stack_to_locals(frame, nargs)?;
 
-                    self.stack.push(invoked_method_frame);
-                    // Currently handled recursively, maybe it could be done iteratively?
-                    let result = self.execute()?;
+                    let class = frame.class.clone();
+                    let (method_index, pc) = (frame.method_index, frame.ip);
 
-                    let frame_mut = self.top_frame_mut();
+                    let call_site = self.call_site(&class, method_index, pc, index)?;
+                    let result = self.invoke_call_site(&call_site, &args)?;
+
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.push_stack(result);
+                    frame_mut.inc_ip(5);
+                },
+
+                GETSTATIC => {
+                    let field_index_byte1 = frame.code[frame.ip + 1];
+                    let field_index_byte2 = frame.code[frame.ip + 2];
+                    let field_index = u16::from_be_bytes([field_index_byte1, field_index_byte2]);
+
+                    let field_ref = frame.class.const_pool.resolve_field(field_index as usize)?;
+                    let (class, index) = self.resolve_static_field(&field_ref)?;
+                    self.initialize_class(&class)?;
+
+                    let value = class.get_static(index);
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(value);
+                    frame_mut.inc_ip(3);
+                },
+
+                PUTSTATIC => {
+                    let field_index_byte1 = frame.code[frame.ip + 1];
+                    let field_index_byte2 = frame.code[frame.ip + 2];
+                    let field_index = u16::from_be_bytes([field_index_byte1, field_index_byte2]);
+
+                    let field_ref = frame.class.const_pool.resolve_field(field_index as usize)?;
+                    let value = frame.pop_stack()?;
+
+                    let (class, index) = self.resolve_static_field(&field_ref)?;
+                    self.initialize_class(&class)?;
+
+                    class.set_static(index, value);
+
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.inc_ip(3);
                 },
 
@@ -522,18 +1327,20 @@ impl JThread {
                     let field_index_byte2 = frame.code[frame.ip + 2];
                     let field_index = u16::from_be_bytes([field_index_byte1, field_index_byte2]);
 
-                    let obj_ref = match frame.pop_stack()? {
-                        JTypeValue::Ref(r) => r,
-                        _ => panic!("GETFIELD called on value type different than object ref")
-                    };
+                    let field_ref = frame.class.const_pool.resolve_field(field_index as usize)?;
 
-                    let value = {
-                        let heap = RefCell::borrow(&self.heap);
-                        let object = heap.get_obj(obj_ref);
-                        object.field_value(field_index as usize)
-                    };
+                    let obj_ref = frame.pop_ref()?;
+
+                    let offset = self.resolve_instance_field(&field_ref)?;
+
+                    if obj_ref == 0 {
+                        let message = format!("Cannot read field \"{}\"", field_ref.field_name);
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into());
+                    }
 
-                    let frame_mut = self.top_frame_mut();
+                    let value = RefCell::borrow(&self.heap).get_obj(obj_ref)?.field_value(offset);
+
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.push_stack(value);
                     frame_mut.inc_ip(3);
                 },
@@ -543,29 +1350,27 @@ impl JThread {
                     let field_index_byte2 = frame.code[frame.ip + 2];
                     let field_index = u16::from_be_bytes([field_index_byte1, field_index_byte2]);
 
+                    let field_ref = frame.class.const_pool.resolve_field(field_index as usize)?;
+
                     let val = frame.pop_stack()?;
 
-                    let obj_ref = match frame.pop_stack()? {
-                        JTypeValue::Ref(r) => r,
-                        _ => { panic!("PUTFIELD called on value type different than object ref") }
-                    };
+                    let obj_ref = frame.pop_ref()?;
+
+                    let offset = self.resolve_instance_field(&field_ref)?;
 
-                    {
-                        let mut heap = self.heap.borrow_mut();
-                        let object = heap.get_obj_mut(obj_ref);
-                        object.fields.insert(field_index as usize, val);
+                    if obj_ref == 0 {
+                        let message = format!("Cannot assign field \"{}\"", field_ref.field_name);
+                        return Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into());
                     }
 
-                    let frame_mut = self.top_frame_mut();
+                    self.heap.borrow_mut().get_obj_mut(obj_ref)?.set_field(offset, val);
+
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.inc_ip(3);
                 },
 
                 IFEQ..=IFLE => {
-
-                    let val = match frame.pop_stack()? {
-                        JTypeValue::Int(i) => i,
-                        _ => { panic!("popped value must be an int") }
-                    };
+                    let val = frame.pop_int()?;
 
                     let result = match op {
                         IFEQ => val == 0,
@@ -574,15 +1379,13 @@ impl JThread {
                         IFLE => val <= 0,
                         IFGT => val > 0,
                         IFGE => val >= 0,
-                        _ => { panic!("impossible - op is already within expected range")}
+                        _ => return Err(anyhow!("impossible - op {} is outside the expected range", op))
                     };
 
                     if result {
                         // Jump to the provided branch
-                        let branch_byte_1 = frame.code[frame.ip + 1];
-                        let branch_byte_2: u8 = frame.code[frame.ip + 2];
-                        let branch_index = u16::from_be_bytes([branch_byte_1, branch_byte_2]);
-                        frame.ip = branch_index as usize;
+                        let offset = frame.read_i16(frame.ip + 1);
+                        frame.jump(offset as i32);
                     } else {
                         // Just move ahead
                         frame.inc_ip(3);
@@ -590,14 +1393,8 @@ impl JThread {
                 },
 
                 IF_ICMPEQ..=IF_ICMPLE => {
-                    let val2 = match frame.pop_stack()? {
-                        JTypeValue::Int(i) => i,
-                        _ => { panic!("popped value must be an int") }
-                    };
-                    let val1 = match frame.pop_stack()? {
-                        JTypeValue::Int(i) => i,
-                        _ => { panic!("popped value must be an int") }
-                    };
+                    let val2 = frame.pop_int()?;
+                    let val1 = frame.pop_int()?;
 
                     let result = match op {
                         IF_ICMPEQ => val1 == val2,
@@ -606,15 +1403,13 @@ impl JThread {
                         IF_ICMPLE => val1 <= val2,
                         IF_ICMPGT => val1 > val2,
                         IF_ICMPGE => val1 >= val2,
-                        _ =>  { panic!("impossible - op is already within expected range")}
+                        _ => return Err(anyhow!("impossible - op {} is outside the expected range", op))
                     };
 
                     if result {
                         // Jump to the provided branch
-                        let branch_byte_1 = frame.code[frame.ip + 1];
-                        let branch_byte_2: u8 = frame.code[frame.ip + 2];
-                        let branch_index = u16::from_be_bytes([branch_byte_1, branch_byte_2]);
-                        frame.ip = branch_index as usize;
+                        let offset = frame.read_i16(frame.ip + 1);
+                        frame.jump(offset as i32);
                     } else {
                         // Just move ahead
                         frame.inc_ip(3);
@@ -622,27 +1417,19 @@ impl JThread {
                 },
 
                 IF_ACMPEQ..=IF_ACMPNE => {
-                    let val2 = match frame.pop_stack()? {
-                        JTypeValue::Ref(r) => r,
-                        _ => panic!("popped value must be an int")
-                    };
-                    let val1 = match frame.pop_stack()? {
-                        JTypeValue::Ref(r) => r,
-                        _ => panic!("popped value must be an int")
-                    };
+                    let val2 = frame.pop_ref()?;
+                    let val1 = frame.pop_ref()?;
 
                     let result = match op {
                         IF_ACMPEQ => val1 == val2,
                         IF_ACMPNE => val1 != val2,
-                        _ => panic!("impossible - op is already within expected range")
+                        _ => return Err(anyhow!("impossible - op {} is outside the expected range", op))
                     };
 
                     if result {
                         // Jump to the provided branch
-                        let branch_byte_1 = frame.code[frame.ip + 1];
-                        let branch_byte_2: u8 = frame.code[frame.ip + 2];
-                        let branch_index = u16::from_be_bytes([branch_byte_1, branch_byte_2]);
-                        frame.ip = branch_index as usize;
+                        let offset = frame.read_i16(frame.ip + 1);
+                        frame.jump(offset as i32);
                     } else {
                         // Just move ahead
                         frame.inc_ip(3);
@@ -655,81 +1442,258 @@ impl JThread {
                     let result = match op {
                         IFNULL => val == NULL_REF,
                         IFNONNULL => val != NULL_REF,
-                        _ => panic!("impossible - op is already within expected range")
+                        _ => return Err(anyhow!("impossible - op {} is outside the expected range", op))
                     };
 
                     if result {
                         // Jump to the provided branch
-                        let branch_byte_1 = frame.code[frame.ip + 1];
-                        let branch_byte_2: u8 = frame.code[frame.ip + 2];
-                        let branch_index = u16::from_be_bytes([branch_byte_1, branch_byte_2]);
-                        frame.ip = branch_index as usize;
+                        let offset = frame.read_i16(frame.ip + 1);
+                        frame.jump(offset as i32);
                     } else {
                         // Just move ahead
                         frame.inc_ip(3);
                     }
                 },
 
-                NEWARRAY => {
-                    let count = frame.pop_int();
+                GOTO => {
+                    let offset = frame.read_i16(frame.ip + 1);
+                    frame.jump(offset as i32);
+                },
+
+                GOTO_W => {
+                    let offset = frame.read_i32(frame.ip + 1);
+                    frame.jump(offset);
+                },
+
+                // The operands of both switches start at the next multiple of 4 after the opcode, counted from the start
+                // of the method's code, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.tableswitch
+                TABLESWITCH => {
+                    let key = frame.pop_int()?;
+
+                    let operands = (frame.ip + 4) & !3;
+                    let default = frame.read_i32(operands);
+                    let low = frame.read_i32(operands + 4);
+                    let high = frame.read_i32(operands + 8);
+
+                    let offset = if key < low || key > high {
+                        default
+                    } else {
+                        frame.read_i32(operands + 12 + 4 * (key - low) as usize)
+                    };
+
+                    frame.jump(offset);
+                },
+
+                LOOKUPSWITCH => {
+                    let key = frame.pop_int()?;
+
+                    let operands = (frame.ip + 4) & !3;
+                    let default = frame.read_i32(operands);
+                    let npairs = frame.read_i32(operands + 4) as usize;
+
+                    // The pairs are sorted by their match value
+                    let pairs: Vec<(i32, i32)> = (0..npairs)
+                        .map(|i| operands + 8 + 8 * i)
+                        .map(|pair| (frame.read_i32(pair), frame.read_i32(pair + 4)))
+                        .collect();
+
+                    let offset = match pairs.binary_search_by_key(&key, |(value, _)| *value) {
+                        Ok(i) => pairs[i].1,
+                        Err(_) => default
+                    };
+
+                    frame.jump(offset);
+                },
+
+                // Subroutines push the address of the following instruction, which is saved to a local variable
+                // with astore and later returned to with ret
+                JSR | JSR_W => {
+                    Self::check_subroutines(frame, op)?;
+
+                    let (offset, length) = match op {
+                        JSR => (frame.read_i16(frame.ip + 1) as i32, 3),
+                        _ => (frame.read_i32(frame.ip + 1), 5)
+                    };
+
+                    frame.push_stack(JTypeValue::RetAddr((frame.ip + length) as u32));
+                    frame.jump(offset);
+                },
+
+                RET => {
+                    Self::check_subroutines(frame, op)?;
+
+                    let index = frame.code[frame.ip + 1] as usize;
+                    frame.ip = Self::return_address(frame, index)?;
+                },
+
+                // wide followed by an instruction taking a local variable index gives it a 16-bit index, and iinc
+                // a 16-bit increment, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.wide
+                WIDE => {
+                    let modified = frame.code[frame.ip + 1];
+                    let index = u16::from_be_bytes([frame.code[frame.ip + 2], frame.code[frame.ip + 3]]) as usize;
+
+                    match modified {
+                        ALOAD | ILOAD | LLOAD | FLOAD | DLOAD => {
+                            let var = frame.locals[index];
+                            frame.push_stack(var);
+                            frame.inc_ip(4);
+                        },
+                        ASTORE | ISTORE | LSTORE | FSTORE | DSTORE => {
+                            let v = frame.pop_stack()?;
+                            frame.locals[index] = v;
+                            frame.inc_ip(4);
+                        },
+                        IINC => {
+                            let increment = frame.read_i16(frame.ip + 4);
+                            frame.locals[index] = (frame.locals[index] + JTypeValue::Int(increment as i32))?;
+                            frame.inc_ip(6);
+                        },
+                        RET => {
+                            Self::check_subroutines(frame, modified)?;
+                            frame.ip = Self::return_address(frame, index)?;
+                        },
+                        _ => return Err(anyhow!("wide cannot modify opcode {}", modified))
+                    }
+                },
+
+                // Unlike instanceof, a null reference passes any cast
+                CHECKCAST => {
+                    let class_index = frame.read_u16(frame.ip + 1) as usize;
+                    let class_name = frame.class.const_pool.resolve_class_name(class_index)?;
+
+                    let obj_ref = match frame.operand_stack.last() {
+                        Some(JTypeValue::Ref(r)) => *r,
+                        v => return Err(anyhow!("CHECKCAST called on {:?}", v))
+                    };
+
+                    self.load_element_class(&class_name)?;
+
+                    if obj_ref != 0 {
+                        let object_class = RefCell::borrow(&self.heap).class_name(obj_ref)?;
 
-                    // TODO if count < 0, throw NegativeArraySizeException
+                        if !self.is_assignable(&object_class, &class_name)? {
+                            return Err(JavaException::new(CLASS_CAST_EXCEPTION, class_cast_message(&object_class, &class_name)).into());
+                        }
+                    }
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.inc_ip(3);
+                },
+
+                NEWARRAY => {
+                    let count = array_size(frame.pop_int()?)?;
+
+                    let component = match frame.code[frame.ip + 1] {
+                        4 => b'Z',
+                        5 => b'C',
+                        6 => b'F',
+                        7 => b'D',
+                        8 => b'B',
+                        9 => b'S',
+                        10 => b'I',
+                        11 => b'J',
+                        atype => return Err(anyhow!("invalid newarray type {}", atype))
+                    };
 
-                    let array = Array::new(count as usize);
+                    let array = Array::new_primitive(component, count)?;
                     let arr_ref = self.heap.borrow_mut().allocate_arr(array);
 
-                    let frame_mut = self.top_frame_mut();
+                    let frame_mut = self.top_frame_mut()?;
                     frame_mut.push_stack(JTypeValue::Ref(arr_ref));
                     frame_mut.inc_ip(2);
                 },
 
+                ANEWARRAY => {
+                    let count = array_size(frame.pop_int()?)?;
 
+                    let class_index = frame.read_u16(frame.ip + 1) as usize;
+                    let component = frame.class.const_pool.resolve_class_name(class_index)?;
 
+                    let arr_ref = self.new_reference_array(&component, count)?;
 
-                _ => {
-                    println!("unknown opcode {}", op);
-                    panic!("unknown opcode!")
-                }
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(JTypeValue::Ref(arr_ref));
+                    frame_mut.inc_ip(3);
+                },
+
+                MULTIANEWARRAY => {
+                    let class_index = frame.read_u16(frame.ip + 1) as usize;
+                    let class_name = frame.class.const_pool.resolve_class_name(class_index)?;
+                    let dimensions = frame.code[frame.ip + 3] as usize;
+
+                    // The count of the outermost dimension is the deepest on the stack
+                    let mut counts = vec![0; dimensions];
+                    for count in counts.iter_mut().rev() {
+                        *count = frame.pop_int()?;
+                    }
+
+                    let arr_ref = self.new_multi_array(&class_name, &counts)?;
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(JTypeValue::Ref(arr_ref));
+                    frame_mut.inc_ip(4);
+                },
+
+                INSTANCEOF => {
+                    let obj_ref = frame.pop_ref()?;
+
+                    let class_index = frame.read_u16(frame.ip + 1) as usize;
+                    let class_name = frame.class.const_pool.resolve_class_name(class_index)?;
+                    self.load_element_class(&class_name)?;
+
+                    let is_instance = obj_ref != 0 && {
+                        let object_class = RefCell::borrow(&self.heap).class_name(obj_ref)?;
+                        self.is_assignable(&object_class, &class_name)?
+                    };
+
+                    let frame_mut = self.top_frame_mut()?;
+                    frame_mut.push_stack(JTypeValue::Int(is_instance as i32));
+                    frame_mut.inc_ip(3);
+                },
+
+
+
+
+                _ => return Err(anyhow!("unknown opcode {}", op))
             }
         }
     }
 
-    fn get_nargs(desc: &str) -> u32 {
-        let mut nargs = 0;
-        for c in desc[1..].chars() {
-            if c == ')' {
-                break;
-            }
+    // jsr and ret are only allowed in class files older than version 50, which introduced the type checking verifier
+    fn check_subroutines(frame: &Frame, op: u8) -> Result<()> {
+        if frame.class.version_major < 50 {
+            return Ok(());
+        }
+
+        let method = ResolvedMethod { class: frame.class.clone(), index: frame.method_index };
+        let message = format!("Bad instruction: {:02x} in method {} of class file version {}", op, method.signature(), frame.class.version_major);
+        Err(JavaException::new(VERIFY_ERROR, message).into())
+    }
 
-            nargs += 1;
+    fn return_address(frame: &Frame, index: usize) -> Result<usize> {
+        match frame.locals[index] {
+            JTypeValue::RetAddr(address) => Ok(address as usize),
+            v => Err(anyhow!("ret from local variable {} holding {:?} instead of a return address", index, v))
         }
-        nargs
     }
 
-    fn pop_operand_stack_to_locals(frame: &mut Frame, nargs: u32) -> Vec<JTypeValue> {
+    fn get_nargs(desc: &str) -> u32 {
+        param_descriptors(desc).len() as u32
+    }
+
+    fn pop_operand_stack_to_locals(frame: &mut Frame, nargs: u32) -> Result<Vec<JTypeValue>> {
         let mut locals = Vec::new();
         let mut i = 1;
         while i <= nargs {
             match frame.operand_stack.pop() {
-                Some(i) => {
-                    let uses_two_entries = match i {
-                        JTypeValue::Double(_) | JTypeValue::Long(_) => true,
-                        _ => false
-                    };
-
-                    // locals.push(i);
-                    locals.insert(0, i);
-                    if uses_two_entries {
-                        locals.insert(1, JTypeValue::Empty);
-                        // locals.push(JTypeValue::Empty)
-                    }
-                }
-                None => panic!("trying to pop too much from frame operand stack")
+                // build_frame takes care of the second local slot used by longs and doubles
+                Some(i) => locals.insert(0, i),
+                None => return Err(anyhow!("trying to pop too much from frame operand stack"))
             };
 
             i += 1;
         }
-        locals
+        Ok(locals)
     }
 }
 
@@ -742,6 +1706,9 @@ mod tests {
     fn test_get_nargs() {
         let nargs = JThread::get_nargs("(II)I");
         assert_eq!(nargs, 2);
+
+        let nargs = JThread::get_nargs("(J[[ILjava/lang/String;[Ljava/lang/Object;D)V");
+        assert_eq!(nargs, 5);
     }
 
 
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use anyhow::Result;
+use crate::jvm::{JThread, IALOAD, LALOAD, FALOAD, DALOAD, AALOAD, BALOAD, CALOAD, SALOAD, IASTORE, LASTORE, FASTORE, DASTORE,
+                 AASTORE, BASTORE, CASTORE, SASTORE};
+use crate::jvm::exceptions::{JavaException, ARRAY_INDEX_OUT_OF_BOUNDS_EXCEPTION, NEGATIVE_ARRAY_SIZE_EXCEPTION,
+                             NULL_POINTER_EXCEPTION};
+use crate::jvm::objects::Array;
+use crate::jvm::strings::STRING_CLASS;
+use crate::jvm::types::JTypeValue;
+
+/// Name of the class of arrays with the given component type, named the way CONSTANT_Class entries name types,
+/// e.g. [Ljava/lang/String; for java/lang/String and [[I for [I.
+pub fn array_class_name(component: &str) -> String {
+    if component.starts_with('[') {
+        format!("[{}", component)
+    } else {
+        format!("[L{};", component)
+    }
+}
+
+// The component type of an array class as a CONSTANT_Class name, e.g. java/lang/String for [Ljava/lang/String;.
+// Primitive components keep their descriptor, e.g. I for [I.
+fn component_name(class_name: &str) -> &str {
+    let component = &class_name[1..];
+    component.strip_prefix('L').and_then(|c| c.strip_suffix(';')).unwrap_or(component)
+}
+
+/// Checks the count of elements of a new array, throwing NegativeArraySizeException for a negative one.
+pub fn array_size(count: i32) -> Result<usize> {
+    if count < 0 {
+        return Err(JavaException::new(NEGATIVE_ARRAY_SIZE_EXCEPTION, count.to_string()).into());
+    }
+
+    Ok(count as usize)
+}
+
+// What the instruction failed to do to a null array, worded like HotSpot's helpful NullPointerExceptions
+fn null_array_message(op: u8) -> &'static str {
+    match op {
+        IALOAD => "Cannot load from int array",
+        LALOAD => "Cannot load from long array",
+        FALOAD => "Cannot load from float array",
+        DALOAD => "Cannot load from double array",
+        AALOAD => "Cannot load from object array",
+        BALOAD => "Cannot load from byte/boolean array",
+        CALOAD => "Cannot load from char array",
+        SALOAD => "Cannot load from short array",
+        IASTORE => "Cannot store to int array",
+        LASTORE => "Cannot store to long array",
+        FASTORE => "Cannot store to float array",
+        DASTORE => "Cannot store to double array",
+        AASTORE => "Cannot store to object array",
+        BASTORE => "Cannot store to byte/boolean array",
+        CASTORE => "Cannot store to char array",
+        SASTORE => "Cannot store to short array",
+        _ => "Cannot read the array length"
+    }
+}
+
+impl JThread {
+    /// The length of the array used by the instruction, throwing NullPointerException for a null reference.
+    pub fn array_length(&self, op: u8, arr_ref: usize) -> Result<usize> {
+        if arr_ref == 0 {
+            return Err(JavaException::new(NULL_POINTER_EXCEPTION, null_array_message(op).to_string()).into());
+        }
+
+        Ok(RefCell::borrow(&self.heap).get_arr(arr_ref)?.len())
+    }
+
+    /// Checks the index of an element loaded or stored by the instruction, throwing NullPointerException for a
+    /// null reference and ArrayIndexOutOfBoundsException for an index outside of the array.
+    pub fn array_index(&self, op: u8, arr_ref: usize, index: i32) -> Result<usize> {
+        let length = self.array_length(op, arr_ref)?;
+
+        if index < 0 || index as usize >= length {
+            let message = format!("Index {} out of bounds for length {}", index, length);
+            return Err(JavaException::new(ARRAY_INDEX_OUT_OF_BOUNDS_EXCEPTION, message).into());
+        }
+
+        Ok(index as usize)
+    }
+
+    /// Loads the class, or for arrays of references the element class, named the way CONSTANT_Class entries name
+    /// types, e.g. java/lang/String for [[Ljava/lang/String;. Arrays of primitives need nothing loaded.
+    pub fn load_element_class(&self, class_name: &str) -> Result<()> {
+        let element = class_name.trim_start_matches('[');
+
+        if element.len() == class_name.len() {
+            self.load_class(class_name)?;
+        } else if let Some(element_class) = element.strip_prefix('L').and_then(|e| e.strip_suffix(';')) {
+            self.load_class(element_class)?;
+        }
+
+        Ok(())
+    }
+
+    /// Allocates an array of the given class or interface or array component type holding nulls, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.anewarray
+    pub fn new_reference_array(&mut self, component: &str, count: usize) -> Result<usize> {
+        self.load_element_class(component)?;
+
+        let array = Array::new_reference(array_class_name(component).into(), count);
+        Ok(self.heap.borrow_mut().allocate_arr(array))
+    }
+
+    /// Allocates an array of the given array class with one count per dimension, the innermost dimensions not
+    /// counted are left null, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.multianewarray
+    pub fn new_multi_array(&mut self, class_name: &str, counts: &[i32]) -> Result<usize> {
+        self.load_element_class(class_name)?;
+
+        // Every dimension is checked before anything is allocated
+        for count in counts {
+            array_size(*count)?;
+        }
+
+        let count = counts[0] as usize;
+        let component = &class_name[1..];
+
+        let mut array = if component.starts_with('[') || component.starts_with('L') {
+            Array::new_reference(class_name.into(), count)
+        } else {
+            Array::new_primitive(component.as_bytes()[0], count)?
+        };
+
+        if counts.len() > 1 {
+            for index in 0..count {
+                let sub_array = self.new_multi_array(component, &counts[1..])?;
+                array.set(index, JTypeValue::Ref(sub_array))?;
+            }
+        }
+
+        Ok(self.heap.borrow_mut().allocate_arr(array))
+    }
+
+    /// Allocates a java/lang/String[] holding the given texts, e.g. the arguments of `main`.
+    pub fn new_string_array(&mut self, texts: &[&str]) -> Result<usize> {
+        let array_ref = self.new_reference_array(STRING_CLASS, texts.len())?;
+
+        for (index, text) in texts.iter().enumerate() {
+            let string_ref = self.new_string(text)?;
+            self.heap.borrow_mut().get_arr_mut(array_ref)?.set(index, JTypeValue::Ref(string_ref))?;
+        }
+
+        Ok(array_ref)
+    }
+
+    /// Whether a value of the source type can be assigned to the target type, both named the way CONSTANT_Class
+    /// entries name types, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.checkcast
+    pub fn is_assignable(&self, source: &str, target: &str) -> Result<bool> {
+        if !source.starts_with('[') {
+            return Ok(!target.starts_with('[') && self.load_class(source)?.is_subtype_of(target));
+        }
+
+        if !target.starts_with('[') {
+            return Ok(matches!(target, "java/lang/Object" | "java/lang/Cloneable" | "java/io/Serializable"));
+        }
+
+        let source_component = &source[1..];
+        let target_component = &target[1..];
+
+        let is_reference = |component: &str| component.starts_with('[') || component.starts_with('L');
+        match (is_reference(source_component), is_reference(target_component)) {
+            (true, true) => self.is_assignable(component_name(source), component_name(target)),
+            (false, false) => Ok(source_component == target_component),
+            _ => Ok(false)
+        }
+    }
+
+    /// Whether the reference, which must not be null, can be stored into the array of references, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.aastore
+    pub fn can_store_reference(&self, array_ref: usize, value_ref: usize) -> Result<bool> {
+        let (array_class, value_class) = {
+            let heap = RefCell::borrow(&self.heap);
+            (heap.get_arr(array_ref)?.class_name(), heap.class_name(value_ref)?)
+        };
+
+        self.is_assignable(&value_class, component_name(&array_class))
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::error::Error;
+use crate::jvm::types::JTypeValue;
+use crate::jvm::types::{param_descriptors, return_descriptor};
+
+pub const ERROR_CLASS: &str = "java/lang/Error";
+pub const LINKAGE_ERROR: &str = "java/lang/LinkageError";
+pub const NO_CLASS_DEF_FOUND_ERROR: &str = "java/lang/NoClassDefFoundError";
+pub const EXCEPTION_IN_INITIALIZER_ERROR: &str = "java/lang/ExceptionInInitializerError";
+pub const NO_SUCH_FIELD_ERROR: &str = "java/lang/NoSuchFieldError";
+pub const NO_SUCH_METHOD_ERROR: &str = "java/lang/NoSuchMethodError";
+pub const ABSTRACT_METHOD_ERROR: &str = "java/lang/AbstractMethodError";
+pub const INCOMPATIBLE_CLASS_CHANGE_ERROR: &str = "java/lang/IncompatibleClassChangeError";
+pub const INSTANTIATION_ERROR: &str = "java/lang/InstantiationError";
+pub const VERIFY_ERROR: &str = "java/lang/VerifyError";
+pub const NULL_POINTER_EXCEPTION: &str = "java/lang/NullPointerException";
+pub const ARITHMETIC_EXCEPTION: &str = "java/lang/ArithmeticException";
+pub const ARRAY_INDEX_OUT_OF_BOUNDS_EXCEPTION: &str = "java/lang/ArrayIndexOutOfBoundsException";
+pub const NEGATIVE_ARRAY_SIZE_EXCEPTION: &str = "java/lang/NegativeArraySizeException";
+pub const ARRAY_STORE_EXCEPTION: &str = "java/lang/ArrayStoreException";
+pub const ILLEGAL_MONITOR_STATE_EXCEPTION: &str = "java/lang/IllegalMonitorStateException";
+pub const STACK_OVERFLOW_ERROR: &str = "java/lang/StackOverflowError";
+pub const CLONE_NOT_SUPPORTED_EXCEPTION: &str = "java/lang/CloneNotSupportedException";
+pub const CLASS_CAST_EXCEPTION: &str = "java/lang/ClassCastException";
+pub const BOOTSTRAP_METHOD_ERROR: &str = "java/lang/BootstrapMethodError";
+pub const WRONG_METHOD_TYPE_EXCEPTION: &str = "java/lang/invoke/WrongMethodTypeException";
+pub const UNSATISFIED_LINK_ERROR: &str = "java/lang/UnsatisfiedLinkError";
+
+/// A Java exception raised by the VM itself, e.g. a linkage error during resolution.
+#[derive(Debug, Clone)]
+pub struct JavaException {
+    pub class_name: &'static str,
+    pub message: String,
+    pub cause: Option<Box<JavaException>>,
+    // The Java object of an exception which ended the thread, returned by `JVM::run` together with it
+    pub exception: Option<JTypeValue>,
+}
+
+impl JavaException {
+    pub fn new(class_name: &'static str, message: String) -> Self {
+        Self { class_name, message, cause: None, exception: None }
+    }
+
+    pub fn with_cause(class_name: &'static str, message: String, cause: JavaException) -> Self {
+        Self { class_name, message, cause: Some(Box::new(cause)), exception: None }
+    }
+}
+
+impl fmt::Display for JavaException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message.as_str() {
+            "" => write!(f, "{}", self.class_name.replace('/', ".")),
+            message => write!(f, "{}: {}", self.class_name.replace('/', "."), message)
+        }
+    }
+}
+
+impl Error for JavaException {}
+
+/// A Java exception object thrown by athrow, while it unwinds the stack looking for a handler. One that is never
+/// caught ends the thread and is returned by `JVM::run`.
+#[derive(Debug, Clone)]
+pub struct ThrownException {
+    pub exception: JTypeValue,
+    pub class_name: String,
+    // The detail message at the time it was thrown
+    pub message: Option<String>,
+}
+
+impl fmt::Display for ThrownException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {}", self.class_name.replace('/', "."), message),
+            None => write!(f, "{}", self.class_name.replace('/', "."))
+        }
+    }
+}
+
+impl Error for ThrownException {}
+
+/// The Java exception a failed resolution threw, later attempts to resolve the same entry throw it again.
+#[derive(Debug, Clone)]
+pub enum ResolutionError {
+    Raised(JavaException),
+    Thrown(ThrownException),
+}
+
+impl ResolutionError {
+    pub fn class_name(&self) -> &str {
+        match self {
+            ResolutionError::Raised(exception) => exception.class_name,
+            ResolutionError::Thrown(thrown) => &thrown.class_name
+        }
+    }
+
+    /// The Java exception of an error, None for internal errors of the VM.
+    pub fn of(error: &anyhow::Error) -> Option<Self> {
+        if let Some(exception) = error.downcast_ref::<JavaException>() {
+            Some(ResolutionError::Raised(exception.clone()))
+        } else {
+            error.downcast_ref::<ThrownException>().map(|thrown| ResolutionError::Thrown(thrown.clone()))
+        }
+    }
+}
+
+impl From<ResolutionError> for anyhow::Error {
+    fn from(error: ResolutionError) -> Self {
+        match error {
+            ResolutionError::Raised(exception) => exception.into(),
+            ResolutionError::Thrown(thrown) => thrown.into()
+        }
+    }
+}
+
+/// Formats a failed cast the way HotSpot does, e.g. 'class A cannot be cast to class java.lang.String (A is in unnamed
+/// module of loader 'app'; java.lang.String is in module java.base of loader 'bootstrap')'
+pub fn class_cast_message(from: &str, to: &str) -> String {
+    let (from_name, to_name) = (from.replace('/', "."), to.replace('/', "."));
+    let (from_module, to_module) = (module_description(from), module_description(to));
+
+    if from_module == to_module {
+        format!("class {} cannot be cast to class {} ({} and {} are in {})", from_name, to_name, from_name, to_name, from_module)
+    } else {
+        format!("class {} cannot be cast to class {} ({} is in {}; {} is in {})", from_name, to_name, from_name, from_module, to_name, to_module)
+    }
+}
+
+// The runtime library stands in for java.base, every other class is in the unnamed module of the application class
+// loader. Arrays belong to the module of their element type, arrays of primitives to java.base
+fn module_description(class_name: &str) -> &'static str {
+    let java_base = "module java.base of loader 'bootstrap'";
+
+    let element = class_name.trim_start_matches('[');
+    let element = if element.len() == class_name.len() {
+        element
+    } else {
+        match element.strip_prefix('L') {
+            Some(element_class) => element_class,
+            None => return java_base
+        }
+    };
+
+    if element.starts_with("java/") {
+        java_base
+    } else {
+        "unnamed module of loader 'app'"
+    }
+}
+
+/// Formats a method the way HotSpot does in linkage error messages, e.g. 'int Foo.bar(long, java.lang.String)'
+pub fn method_signature(class_name: &str, method_name: &str, desc: &str) -> String {
+    format!("'{} {}'", type_name(return_descriptor(desc)), method_display_name(class_name, method_name, desc))
+}
+
+/// Formats a method the way HotSpot does in NullPointerException messages, e.g. Foo.bar(long, java.lang.String)
+pub fn method_display_name(class_name: &str, method_name: &str, desc: &str) -> String {
+    let param_names: Vec<String> = param_descriptors(desc).into_iter()
+        .map(type_name)
+        .collect();
+
+    format!("{}.{}({})", class_name.replace('/', "."), method_name, param_names.join(", "))
+}
+
+/// Formats a method descriptor the way MethodType.toString does, e.g. (int,String)void
+pub fn method_type_name(desc: &str) -> String {
+    let simple_name = |desc: &str| {
+        let name = type_name(desc);
+        match name.rfind('.') {
+            Some(i) => name[i + 1..].to_string(),
+            None => name
+        }
+    };
+
+    let param_names: Vec<String> = param_descriptors(desc).into_iter()
+        .map(simple_name)
+        .collect();
+
+    format!("({}){}", param_names.join(","), simple_name(return_descriptor(desc)))
+}
+
+// Returns the Java name of the type with the given descriptor
+fn type_name(desc: &str) -> String {
+    match desc.as_bytes()[0] {
+        b'B' => "byte".to_string(),
+        b'C' => "char".to_string(),
+        b'D' => "double".to_string(),
+        b'F' => "float".to_string(),
+        b'I' => "int".to_string(),
+        b'J' => "long".to_string(),
+        b'S' => "short".to_string(),
+        b'Z' => "boolean".to_string(),
+        b'V' => "void".to_string(),
+        b'[' => {
+            format!("{}[]", type_name(&desc[1..]))
+        },
+        _ => {
+            let end = desc.find(';').unwrap_or(desc.len());
+            desc[1..end].replace('/', ".")
+        }
+    }
+}
@@ -1,11 +1,13 @@
-use crate::class::Class;
+use crate::jvm::method_area::RuntimeClass;
 use std::rc::Rc;
 use anyhow::{Result, anyhow};
 use crate::jvm::JTypeValue;
 
 #[derive(Debug)]
 pub struct Frame {
-    pub class: Rc<Class>,
+    pub class: Rc<RuntimeClass>,
+    // index of the executed method within class.methods
+    pub method_index: usize,
     pub ip: usize,
     pub code: Vec<u8>,
     pub locals: Vec<JTypeValue>,
@@ -13,9 +15,10 @@ pub struct Frame {
 }
 
 impl Frame {
-    pub fn new(class: Rc<Class>, code: Vec<u8>, locals: Vec<JTypeValue>) -> Self {
+    pub fn new(class: Rc<RuntimeClass>, method_index: usize, code: Vec<u8>, locals: Vec<JTypeValue>) -> Self {
         Self {
             class,
+            method_index,
             code,
             ip: 0,
             locals,
@@ -30,23 +33,24 @@ impl Frame {
         }
     }
 
-    pub fn pop_int(&mut self) -> i32 {
-        match self.operand_stack.pop() {
-            Some(v) => match v {
-                JTypeValue::Int(i) => i,
-                _ => panic!("tried popping int but something else found")
-            },
-            None => panic!("tied popping int but nothing found")
+    pub fn pop_int(&mut self) -> Result<i32> {
+        match self.pop_stack()? {
+            JTypeValue::Int(i) => Ok(i),
+            v => Err(anyhow!("tried popping int but found {:?}", v))
         }
     }
 
-    pub fn pop_ref(&mut self) -> usize {
-        match self.operand_stack.pop() {
-            Some(v) => match v {
-                JTypeValue::Ref(r) => r,
-                _ => panic!("tried popping ref but something else found")
-            },
-            None => panic!("tried popping ref but nothing found")
+    pub fn pop_long(&mut self) -> Result<i64> {
+        match self.pop_stack()? {
+            JTypeValue::Long(l) => Ok(l),
+            v => Err(anyhow!("tried popping long but found {:?}", v))
+        }
+    }
+
+    pub fn pop_ref(&mut self) -> Result<usize> {
+        match self.pop_stack()? {
+            JTypeValue::Ref(r) => Ok(r),
+            v => Err(anyhow!("tried popping ref but found {:?}", v))
         }
     }
 
@@ -59,7 +63,67 @@ impl Frame {
         self.operand_stack.push(v );
     }
 
+    /// Pops the values making up the given number of words off the operand stack.
+    pub fn pop_words(&mut self, words: usize) -> Result<()> {
+        let entries = self.entries(0, words)?;
+        self.operand_stack.truncate(self.operand_stack.len() - entries);
+        Ok(())
+    }
+
+    /// Duplicates the values making up the top `words` words of the operand stack and inserts the copies below the
+    /// values making up the next `below` words.
+    pub fn dup_words(&mut self, words: usize, below: usize) -> Result<()> {
+        let top = self.entries(0, words)?;
+        let skipped = self.entries(top, below)?;
+
+        let len = self.operand_stack.len();
+        let copies = self.operand_stack[len - top..].to_vec();
+        let at = len - top - skipped;
+        self.operand_stack.splice(at..at, copies);
+        Ok(())
+    }
+
+    // The number of stack entries making up the given number of words, counting down from `skip` entries below the top.
+    // Longs and doubles are a single entry but two words, which must not be split.
+    fn entries(&self, skip: usize, words: usize) -> Result<usize> {
+        let mut entries = 0;
+        let mut counted = 0;
+
+        while counted < words {
+            let value = match self.operand_stack.len().checked_sub(skip + entries + 1) {
+                Some(i) => self.operand_stack[i],
+                None => return Err(anyhow!("operand stack {:?} holds less than {} words", self.operand_stack, skip + words))
+            };
+
+            counted += value.category();
+            entries += 1;
+        }
+
+        if counted != words {
+            return Err(anyhow!("a long or double on the operand stack {:?} would be split", self.operand_stack));
+        }
+
+        Ok(entries)
+    }
+
     pub fn inc_ip(&mut self, inc: usize) {
         self.ip += inc;
     }
+
+    /// Moves the instruction pointer by a signed offset relative to the current instruction.
+    pub fn jump(&mut self, offset: i32) {
+        self.ip = (self.ip as isize + offset as isize) as usize;
+    }
+
+    pub fn read_u16(&self, pos: usize) -> u16 {
+        u16::from_be_bytes([self.code[pos], self.code[pos + 1]])
+    }
+
+    pub fn read_i16(&self, pos: usize) -> i16 {
+        i16::from_be_bytes([self.code[pos], self.code[pos + 1]])
+    }
+
+    pub fn read_i32(&self, pos: usize) -> i32 {
+        i32::from_be_bytes([self.code[pos], self.code[pos + 1], self.code[pos + 2], self.code[pos + 3]])
+    }
 }
\ No newline at end of file
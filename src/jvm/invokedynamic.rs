@@ -0,0 +1,396 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use anyhow::{Result, anyhow};
+use crate::class::{Class, Const, ConstPool, Field, BootstrapMethod, MethodHandleRef, ACC_PUBLIC, ACC_PRIVATE, ACC_FINAL, ACC_NATIVE,
+                   ACC_SYNTHETIC, REF_INVOKE_STATIC, REF_NEW_INVOKE_SPECIAL};
+use crate::jvm::JThread;
+use crate::jvm::method_area::{RuntimeClass, ResolvedMethod};
+use crate::jvm::objects::Object;
+use crate::jvm::types::{JTypeValue, param_descriptors, return_descriptor};
+use crate::jvm::strings::{format_double, format_float};
+use crate::jvm::exceptions::{JavaException, ThrownException, BOOTSTRAP_METHOD_ERROR};
+
+const STRING_CONCAT_FACTORY: &str = "java/lang/invoke/StringConcatFactory";
+const LAMBDA_METAFACTORY: &str = "java/lang/invoke/LambdaMetafactory";
+const LOOKUP_CLASS: &str = "java/lang/invoke/MethodHandles$Lookup";
+
+// Flags passed to LambdaMetafactory.altMetafactory
+const FLAG_SERIALIZABLE: i32 = 1;
+const FLAG_MARKERS: i32 = 2;
+const FLAG_BRIDGES: i32 = 4;
+
+// Tags used by StringConcatFactory recipes, see java.lang.invoke.StringConcatFactory#makeConcatWithConstants
+const TAG_ARG: char = '\u{1}';
+const TAG_CONST: char = '\u{2}';
+
+/// A linked invokedynamic call site, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.invokedynamic
+///
+/// Instead of running the bootstrap method in Java, the VM recognizes the supported bootstrap methods and links
+/// the call site to an equivalent built-in implementation.
+#[derive(Debug)]
+pub enum CallSite {
+    // Bootstrapped by StringConcatFactory, static constants are already inlined into the recipe
+    StringConcat { recipe: Vec<RecipePart>, arg_types: Vec<String> },
+    // Bootstrapped by LambdaMetafactory, every invocation creates an instance of the class holding the captured arguments
+    Lambda(Rc<RuntimeClass>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecipePart {
+    Literal(String),
+    Argument,
+}
+
+impl JThread {
+    /// Returns the call site of the invokedynamic instruction at `pc`, linking it on first execution.
+    /// Every invokedynamic instruction is a separate call site, even if it shares the constant pool entry.
+    pub fn call_site(&mut self, class: &Rc<RuntimeClass>, method_index: usize, pc: usize, cp_index: usize) -> Result<Rc<CallSite>> {
+        if let Some(call_site) = class.call_site(method_index, pc) {
+            return Ok(call_site);
+        }
+
+        let call_site = Rc::new(self.link_call_site(class, cp_index)?);
+        class.set_call_site(method_index, pc, call_site.clone());
+        Ok(call_site)
+    }
+
+    pub fn invoke_call_site(&mut self, call_site: &CallSite, args: &[JTypeValue]) -> Result<JTypeValue> {
+        match call_site {
+            CallSite::StringConcat { recipe, arg_types } => {
+                let mut result = String::new();
+                let mut args = args.iter().zip(arg_types);
+
+                for part in recipe {
+                    match part {
+                        RecipePart::Literal(text) => result.push_str(text),
+                        RecipePart::Argument => {
+                            let (value, desc) = match args.next() {
+                                Some(arg) => arg,
+                                None => return Err(anyhow!("string concatenation recipe has more arguments than the call site"))
+                            };
+                            let text = self.string_value_of(*value, desc)?;
+                            result.push_str(&text);
+                        }
+                    }
+                }
+
+                Ok(JTypeValue::Ref(self.new_string(&result)?))
+            },
+            CallSite::Lambda(lambda_class) => {
+                // Captured arguments are the only fields of the lambda class, so they take the first offsets
+                let mut lambda = Object::new(lambda_class.clone());
+                for (offset, value) in args.iter().enumerate() {
+                    lambda.set_field(offset, *value);
+                }
+
+                Ok(JTypeValue::Ref(self.heap.borrow_mut().allocate_obj(lambda)))
+            }
+        }
+    }
+
+    /// Runs the functional interface method of a lambda object by calling the implementation method with the captured
+    /// arguments followed by the arguments of the call, boxing, unboxing and widening them where the types differ.
+    pub fn invoke_lambda(&mut self, target: &MethodHandleRef, method: &ResolvedMethod, args: &[JTypeValue]) -> Result<JTypeValue> {
+        let lambda_ref = match args[0] {
+            JTypeValue::Ref(r) => r,
+            v => return Err(anyhow!("lambda invoked on {:?}", v))
+        };
+
+        // Parameter types of the implementation method, including the receiver of instance methods
+        let receiver_type = format!("L{};", target.class_name);
+        let mut target_types = match target.kind {
+            REF_INVOKE_STATIC | REF_NEW_INVOKE_SPECIAL => Vec::new(),
+            _ => vec![receiver_type.as_str()]
+        };
+        target_types.extend(param_descriptors(&target.desc));
+
+        let mut target_args = RefCell::borrow(&self.heap).get_obj(lambda_ref)?.fields.clone();
+        let arg_types = param_descriptors(&method.method().descriptor);
+        if target_args.len() + arg_types.len() != target_types.len() {
+            return Err(anyhow!("lambda target {}.{}{} does not match {}", target.class_name, target.name, target.desc, method.signature()));
+        }
+
+        for ((value, from), to) in args[1..].iter().zip(arg_types).zip(&target_types[target_args.len()..]) {
+            target_args.push(self.adapt(*value, from, to)?);
+        }
+
+        let result = self.invoke_method_handle(target, &target_args)?;
+
+        let returned = match target.kind {
+            REF_NEW_INVOKE_SPECIAL => receiver_type.as_str(),
+            _ => return_descriptor(&target.desc)
+        };
+
+        match return_descriptor(&method.method().descriptor) {
+            "V" => Ok(JTypeValue::Empty),
+            expected => self.adapt(result, returned, expected)
+        }
+    }
+
+    /// Resolves a dynamically-computed constant by invoking its bootstrap method with the lookup, the name and the type
+    /// of the constant followed by the static arguments, see https://docs.oracle.com/javase/specs/jvms/se11/html/jvms-5.html#jvms-5.4.3.6
+    pub fn resolve_dynamic_constant(&mut self, class: &Rc<RuntimeClass>, cp_index: usize) -> Result<JTypeValue> {
+        let (bootstrap_index, name, desc) = class.const_pool.resolve_dynamic(cp_index)?;
+
+        let bootstrap = match class.bootstrap_methods.get(bootstrap_index as usize) {
+            Some(b) => b,
+            None => return Err(anyhow!("{} has no bootstrap method {}", class.name, bootstrap_index))
+        };
+        let handle = class.const_pool.resolve_method_handle(bootstrap.method_ref as usize)?;
+
+        // The lookup of the runtime library only knows its class, bootstrap methods cannot find members with it
+        let lookup_class = JTypeValue::Ref(self.class_mirror(&class.name)?);
+        let lookup = self.new_vm_object(LOOKUP_CLASS, &[("lookupClass", "Ljava/lang/Class;", lookup_class)])?;
+
+        let type_mirror = self.type_mirror(&desc)?;
+        let mut args = vec![lookup, JTypeValue::Ref(self.new_string(&name)?), JTypeValue::Ref(type_mirror)];
+        let mut call_desc = String::from("(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;");
+
+        for arg_index in bootstrap.arguments.iter().map(|i| *i as usize) {
+            args.push(self.load_constant(class, arg_index)?);
+            call_desc.push_str(&Self::constant_type(class, arg_index)?);
+        }
+        call_desc.push(')');
+        call_desc.push_str(&desc);
+
+        let result = self.resolve_handle_member(&handle)
+            .and_then(|_| self.invoke_with_conversions(&handle, &Self::handle_type(&handle), &call_desc, &args));
+
+        let err = match result {
+            Ok(value) => return Ok(value),
+            Err(e) => e
+        };
+
+        // Errors are passed on as they are, anything else thrown while bootstrapping is wrapped
+        let message = "bootstrap method initialization exception";
+        if let Some(thrown) = err.downcast_ref::<ThrownException>() {
+            if self.is_error(&thrown.class_name)? {
+                return Err(err);
+            }
+
+            return Err(self.wrap_thrown(BOOTSTRAP_METHOD_ERROR, message, thrown)?.into());
+        }
+
+        match err.downcast::<JavaException>() {
+            Ok(exception) if !self.is_error(exception.class_name)? => {
+                Err(JavaException::with_cause(BOOTSTRAP_METHOD_ERROR, message.to_string(), exception).into())
+            },
+            Ok(exception) => Err(exception.into()),
+            Err(e) => Err(e)
+        }
+    }
+
+    // The field descriptor of the value a loadable constant pool entry resolves to
+    fn constant_type(class: &RuntimeClass, index: usize) -> Result<String> {
+        let desc = match class.const_pool.resolve(index)? {
+            Const::Integer(_) => "I".to_string(),
+            Const::Float(_) => "F".to_string(),
+            Const::Long(_) => "J".to_string(),
+            Const::Double(_) => "D".to_string(),
+            Const::StringIndex(_) => "Ljava/lang/String;".to_string(),
+            Const::ClassIndex(_) => "Ljava/lang/Class;".to_string(),
+            Const::MethodHandle(_, _) => "Ljava/lang/invoke/MethodHandle;".to_string(),
+            Const::MethodType(_) => "Ljava/lang/invoke/MethodType;".to_string(),
+            Const::Dynamic(_, _) => class.const_pool.resolve_dynamic(index)?.2.to_string(),
+            c => return Err(anyhow!("{:?} is not a loadable constant", c))
+        };
+
+        Ok(desc)
+    }
+
+    fn link_call_site(&mut self, class: &RuntimeClass, cp_index: usize) -> Result<CallSite> {
+        let (bootstrap_index, name, desc) = class.const_pool.resolve_invoke_dynamic(cp_index)?;
+
+        let bootstrap = match class.bootstrap_methods.get(bootstrap_index as usize) {
+            Some(b) => b,
+            None => return Err(anyhow!("{} has no bootstrap method {}", class.name, bootstrap_index))
+        };
+        let handle = class.const_pool.resolve_method_handle(bootstrap.method_ref as usize)?;
+
+        let arg_types: Vec<String> = param_descriptors(&desc).into_iter().map(String::from).collect();
+
+        match (handle.class_name.as_ref(), handle.name.as_ref()) {
+            (STRING_CONCAT_FACTORY, "makeConcatWithConstants") => {
+                let (recipe_index, constant_indexes) = match bootstrap.arguments.split_first() {
+                    Some(args) => args,
+                    None => return Err(JavaException::new(BOOTSTRAP_METHOD_ERROR, "Missing string concatenation recipe".to_string()).into())
+                };
+
+                let recipe = class.const_pool.resolve_string(*recipe_index as usize)?;
+                let mut constants = Vec::new();
+                for index in constant_indexes {
+                    constants.push(Self::constant_text(class, *index as usize)?);
+                }
+
+                let recipe = Self::parse_recipe(&recipe, &constants)?;
+                Ok(CallSite::StringConcat { recipe, arg_types })
+            },
+            (STRING_CONCAT_FACTORY, "makeConcat") => {
+                let recipe = arg_types.iter().map(|_| RecipePart::Argument).collect();
+                Ok(CallSite::StringConcat { recipe, arg_types })
+            },
+            (LAMBDA_METAFACTORY, "metafactory") => {
+                let lambda_class = self.spin_lambda_class(class, &name, &desc, bootstrap, false)?;
+                Ok(CallSite::Lambda(lambda_class))
+            },
+            (LAMBDA_METAFACTORY, "altMetafactory") => {
+                let lambda_class = self.spin_lambda_class(class, &name, &desc, bootstrap, true)?;
+                Ok(CallSite::Lambda(lambda_class))
+            },
+            (class_name, method_name) => {
+                let message = format!("Unsupported bootstrap method {}.{} for call site {}{}",
+                                      class_name.replace('/', "."), method_name, name, desc);
+                Err(JavaException::new(BOOTSTRAP_METHOD_ERROR, message).into())
+            }
+        }
+    }
+
+    // Generates the class of the lambda objects created by a call site, see java.lang.invoke.LambdaMetafactory.
+    // The class implements the functional interface with a method the VM runs by calling the implementation method.
+    fn spin_lambda_class(&mut self, caller: &RuntimeClass, method_name: &str, desc: &str, bootstrap: &BootstrapMethod,
+                         is_alt: bool) -> Result<Rc<RuntimeClass>> {
+        let bootstrap_arg = |i: usize| match bootstrap.arguments.get(i) {
+            Some(index) => Ok(*index as usize),
+            None => Err(JavaException::new(BOOTSTRAP_METHOD_ERROR, format!("Missing argument {} of LambdaMetafactory", i)))
+        };
+
+        let interface = return_descriptor(desc).trim_start_matches('L').trim_end_matches(';');
+        let sam_desc = caller.const_pool.resolve_method_type(bootstrap_arg(0)?)?;
+        let target = caller.const_pool.resolve_method_handle(bootstrap_arg(1)?)?;
+
+        let mut interfaces: Vec<Rc<str>> = vec![interface.into()];
+        let mut method_descs = vec![sam_desc];
+
+        if is_alt {
+            let flags = Self::int_constant(caller, bootstrap_arg(3)?)?;
+            let mut next = 4;
+
+            if flags & FLAG_SERIALIZABLE != 0 {
+                interfaces.push("java/io/Serializable".into());
+            }
+
+            if flags & FLAG_MARKERS != 0 {
+                let count = Self::int_constant(caller, bootstrap_arg(next)?)? as usize;
+                for i in 0..count {
+                    interfaces.push(caller.const_pool.resolve_class_name(bootstrap_arg(next + 1 + i)?)?);
+                }
+                next += count + 1;
+            }
+
+            if flags & FLAG_BRIDGES != 0 {
+                let count = Self::int_constant(caller, bootstrap_arg(next)?)? as usize;
+                for i in 0..count {
+                    method_descs.push(caller.const_pool.resolve_method_type(bootstrap_arg(next + 1 + i)?)?);
+                }
+            }
+        }
+
+        interfaces.dedup();
+
+        let fields = param_descriptors(desc).into_iter().enumerate()
+            .map(|(i, field_desc)| Field {
+                flags: ACC_PRIVATE | ACC_FINAL,
+                name: format!("arg${}", i + 1).into(),
+                descriptor: field_desc.into(),
+                attributes: Vec::new(),
+            })
+            .collect();
+
+        // Native, as the methods have no bytecode and are run by invoke_lambda instead
+        let methods = method_descs.into_iter()
+            .map(|method_desc| Field {
+                flags: ACC_PUBLIC | ACC_NATIVE | ACC_SYNTHETIC,
+                name: method_name.into(),
+                descriptor: method_desc,
+                attributes: Vec::new(),
+            })
+            .collect();
+
+        let lambda_class = Class {
+            version_major: caller.version_major,
+            version_minor: caller.version_minor,
+            const_pool: ConstPool::default(),
+            name: caller.name.clone(),
+            super_class: Some("java/lang/Object".into()),
+            flags: ACC_FINAL | ACC_SYNTHETIC,
+            interfaces,
+            fields,
+            methods,
+            attributes: Vec::new(),
+            bootstrap_methods: Vec::new(),
+        };
+
+        self.method_area.borrow_mut().define_lambda_class(lambda_class, target)
+    }
+
+    fn int_constant(class: &RuntimeClass, index: usize) -> Result<i32> {
+        match class.const_pool.resolve(index)? {
+            Const::Integer(i) => Ok(*i),
+            c => Err(anyhow!("expected an Integer constant, found {:?}", c))
+        }
+    }
+
+    // Splits the recipe into literal text and argument slots, static constants become part of the literal text
+    fn parse_recipe(recipe: &str, constants: &[String]) -> Result<Vec<RecipePart>> {
+        let mut parts = Vec::new();
+        let mut constants = constants.iter();
+        let mut literal = String::new();
+
+        for c in recipe.chars() {
+            match c {
+                TAG_ARG => {
+                    if !literal.is_empty() {
+                        parts.push(RecipePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(RecipePart::Argument);
+                },
+                TAG_CONST => match constants.next() {
+                    Some(constant) => literal.push_str(constant),
+                    None => return Err(JavaException::new(BOOTSTRAP_METHOD_ERROR, "Missing constant for string concatenation recipe".to_string()).into())
+                },
+                _ => literal.push(c)
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(RecipePart::Literal(literal));
+        }
+
+        Ok(parts)
+    }
+
+    fn constant_text(class: &RuntimeClass, index: usize) -> Result<String> {
+        let text = match class.const_pool.resolve(index)? {
+            Const::StringIndex(_) => class.const_pool.resolve_string(index)?.to_string(),
+            Const::Integer(i) => i.to_string(),
+            Const::Long(l) => l.to_string(),
+            Const::Float(f) => format_float(*f),
+            Const::Double(d) => format_double(*d),
+            c => return Err(anyhow!("unsupported string concatenation constant {:?}", c))
+        };
+
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_recipe() -> Result<()> {
+        let parts = JThread::parse_recipe("a=\u{1}, \u{2}\u{1}\u{1}!", &["b=".to_string()])?;
+
+        assert_eq!(parts, vec![
+            RecipePart::Literal("a=".to_string()),
+            RecipePart::Argument,
+            RecipePart::Literal(", b=".to_string()),
+            RecipePart::Argument,
+            RecipePart::Argument,
+            RecipePart::Literal("!".to_string()),
+        ]);
+
+        Ok(())
+    }
+}
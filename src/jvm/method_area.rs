@@ -0,0 +1,671 @@
+use std::rc::Rc;
+use std::cell::{Cell, RefCell, OnceCell};
+use std::fmt;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::PathBuf;
+use anyhow::Result;
+use crate::class::{Class, Const, Field, MethodHandleRef};
+use crate::jvm::types::JTypeValue;
+use crate::jvm::invokedynamic::CallSite;
+use crate::jvm::exceptions::{JavaException, ResolutionError, method_signature, method_display_name, NO_CLASS_DEF_FOUND_ERROR, NO_SUCH_METHOD_ERROR, INCOMPATIBLE_CLASS_CHANGE_ERROR, VERIFY_ERROR};
+
+// see https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-5.html#jvms-5.5
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClassState {
+    Loaded,
+    BeingInitialized,
+    Initialized,
+    // Initialization completed abruptly, the class cannot be used
+    Erroneous,
+}
+
+/// An instance field slot, objects of a class hold one value per slot of its layout.
+#[derive(Debug, Clone)]
+pub struct FieldSlot {
+    pub class_name: Rc<str>,
+    pub name: Rc<str>,
+    pub descriptor: Rc<str>,
+}
+
+/// A method together with the class declaring it.
+#[derive(Clone)]
+pub struct ResolvedMethod {
+    pub class: Rc<RuntimeClass>,
+    pub index: usize,
+}
+
+impl ResolvedMethod {
+    pub fn method(&self) -> &Field {
+        &self.class.methods[self.index]
+    }
+
+    pub fn signature(&self) -> String {
+        let method = self.method();
+        method_signature(&self.class.name, &method.name, &method.descriptor)
+    }
+
+    pub fn display_name(&self) -> String {
+        let method = self.method();
+        method_display_name(&self.class.name, &method.name, &method.descriptor)
+    }
+
+    pub fn is_signature_polymorphic(&self) -> bool {
+        self.class.find_signature_polymorphic(&self.method().name) == Some(self.index)
+    }
+
+    fn is_same(&self, other: &ResolvedMethod) -> bool {
+        Rc::ptr_eq(&self.class, &other.class) && self.index == other.index
+    }
+}
+
+// Vtables make classes point to themselves, printing the whole class here would never end
+impl fmt::Debug for ResolvedMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = self.method();
+        write!(f, "{}.{}{}", self.class.name, method.name, method.descriptor)
+    }
+}
+
+/// Outcome of method selection, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.6
+#[derive(Debug, Clone)]
+pub enum Selected {
+    Method(ResolvedMethod),
+    // Neither the class nor its superinterfaces provide a non-abstract method
+    Missing,
+    // More than one maximally-specific default method
+    Conflict(Vec<ResolvedMethod>),
+}
+
+/// A loaded and prepared class, shared between frames and heap objects.
+#[derive(Debug)]
+pub struct RuntimeClass {
+    class: Class,
+    pub super_class: Option<Rc<RuntimeClass>>,
+    pub interfaces: Vec<Rc<RuntimeClass>>,
+    pub state: Cell<ClassState>,
+
+    // Inherited slots come first, so a field has the same offset in every subclass
+    pub field_layout: Vec<FieldSlot>,
+
+    // Same as with fields, inherited entries come first and keep their index in subclasses
+    vtable: OnceCell<Vec<ResolvedMethod>>,
+
+    // For every superinterface, the methods selected for each method it declares, in declaration order
+    itable: OnceCell<HashMap<Rc<str>, Vec<Selected>>>,
+
+    // Indexed the same way as `class.fields`, non-static fields hold JTypeValue::Empty
+    static_values: RefCell<Vec<JTypeValue>>,
+
+    // Linked invokedynamic call sites, keyed by method index and the pc of the instruction
+    call_sites: RefCell<HashMap<(usize, usize), Rc<CallSite>>>,
+
+    // For classes spun by LambdaMetafactory, the method their functional interface method calls
+    pub lambda_target: Option<MethodHandleRef>,
+
+    // Outcome of resolving the constant pool entries loaded by ldc which need resolution, keyed by constant pool index
+    resolved_constants: RefCell<HashMap<usize, std::result::Result<JTypeValue, ResolutionError>>>,
+}
+
+impl Deref for RuntimeClass {
+    type Target = Class;
+
+    fn deref(&self) -> &Self::Target {
+        &self.class
+    }
+}
+
+impl RuntimeClass {
+    fn new(class: Class, super_class: Option<Rc<RuntimeClass>>, interfaces: Vec<Rc<RuntimeClass>>) -> Result<Self> {
+        let static_values = Self::prepare_statics(&class)?;
+        let field_layout = Self::layout_fields(&class, &super_class);
+
+        Ok(Self {
+            class,
+            super_class,
+            interfaces,
+            state: Cell::new(ClassState::Loaded),
+            field_layout,
+            vtable: OnceCell::new(),
+            itable: OnceCell::new(),
+            static_values: RefCell::new(static_values),
+            call_sites: RefCell::new(HashMap::new()),
+            lambda_target: None,
+            resolved_constants: RefCell::new(HashMap::new()),
+        })
+    }
+
+    // Builds the vtable and the itable, entries refer back to the class so this can only be done once it is in an Rc
+    fn link(self: &Rc<Self>) -> Result<()> {
+        self.link_vtable()?;
+        self.link_itable();
+        Ok(())
+    }
+
+    fn link_vtable(self: &Rc<Self>) -> Result<()> {
+        let mut vtable = match &self.super_class {
+            Some(super_class) if !self.is_interface() => super_class.vtable().to_vec(),
+            _ => Vec::new()
+        };
+
+        for (index, method) in self.methods.iter().enumerate() {
+            if self.is_interface() || method.is_static() || method.is_private() || method.name.starts_with('<') {
+                continue;
+            }
+
+            let mut overrides = false;
+            for entry in vtable.iter_mut() {
+                if !self.can_override(entry, method) {
+                    continue;
+                }
+
+                if entry.method().is_final() {
+                    let message = format!("class {} overrides final method {}.{}{}", self.name.replace('/', "."),
+                                          entry.class.name.replace('/', "."), method.name, method.descriptor);
+                    return Err(JavaException::new(VERIFY_ERROR, message).into());
+                }
+
+                *entry = ResolvedMethod { class: self.clone(), index };
+                overrides = true;
+            }
+
+            if !overrides {
+                vtable.push(ResolvedMethod { class: self.clone(), index });
+            }
+        }
+
+        self.vtable.set(vtable).expect("class must be linked only once");
+        Ok(())
+    }
+
+    fn link_itable(self: &Rc<Self>) {
+        let mut itable = HashMap::new();
+
+        if !self.is_interface() {
+            for interface in self.superinterfaces() {
+                let selected = interface.methods.iter()
+                    .map(|m| {
+                        if m.is_static() || m.is_private() || m.name.starts_with('<') {
+                            Selected::Missing
+                        } else {
+                            self.select(&m.name, &m.descriptor)
+                        }
+                    })
+                    .collect();
+
+                itable.insert(interface.name.clone(), selected);
+            }
+        }
+
+        self.itable.set(itable).expect("class must be linked only once");
+    }
+
+    // Selection for a resolved method that any public instance method can override, i.e. an interface method
+    fn select(self: &Rc<Self>, name: &str, desc: &str) -> Selected {
+        let mut class = Some(self.clone());
+        while let Some(c) = class {
+            if let Some(index) = c.find_method(name, desc) {
+                let method = &c.methods[index];
+                if !method.is_static() && !method.is_private() {
+                    return Selected::Method(ResolvedMethod { class: c, index });
+                }
+            }
+            class = c.super_class.clone();
+        }
+
+        self.select_default(name, desc)
+    }
+
+    // The last step of selection, picks the only non-abstract maximally-specific superinterface method
+    fn select_default(&self, name: &str, desc: &str) -> Selected {
+        let candidates = self.superinterface_methods(name, desc);
+        let defaults: Vec<ResolvedMethod> = Self::maximally_specific(&candidates).into_iter()
+            .filter(|m| !m.method().is_abstract())
+            .collect();
+
+        match defaults.len() {
+            0 => Selected::Missing,
+            1 => Selected::Method(defaults[0].clone()),
+            _ => Selected::Conflict(defaults)
+        }
+    }
+
+    /// Method selection for invokespecial, this class is the one the lookup starts from, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.invokespecial
+    pub fn select_special(self: &Rc<Self>, name: &str, desc: &str) -> Selected {
+        let mut class = Some(self.clone());
+        while let Some(c) = class {
+            if let Some(index) = c.find_method(name, desc) {
+                if !c.methods[index].is_static() {
+                    return Selected::Method(ResolvedMethod { class: c, index });
+                }
+            }
+
+            // Interfaces only fall back to the public methods of java/lang/Object, which is their super class
+            if c.is_interface() {
+                if let Some(object) = &c.super_class {
+                    if let Some(index) = object.find_method(name, desc) {
+                        let method = &object.methods[index];
+                        if method.is_public() && !method.is_static() {
+                            return Selected::Method(ResolvedMethod { class: object.clone(), index });
+                        }
+                    }
+                }
+                break;
+            }
+
+            class = c.super_class.clone();
+        }
+
+        self.select_default(name, desc)
+    }
+
+    // Overriding, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.5
+    // Package private methods are only overridden from within the same runtime package. The transitive
+    // case is covered by the vtable itself, the entry already holds the latest overrider.
+    fn can_override(&self, entry: &ResolvedMethod, method: &Field) -> bool {
+        let overridden = entry.method();
+
+        overridden.name == method.name
+            && overridden.descriptor == method.descriptor
+            && (overridden.is_public() || overridden.is_protected() || entry.class.package_name() == self.package_name())
+    }
+
+    pub fn vtable(&self) -> &[ResolvedMethod] {
+        self.vtable.get().expect("class must be linked before use")
+    }
+
+    /// Method selection for invokevirtual, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.6
+    /// The receiver is an instance of this class, the resolved method is looked up in the vtable of
+    /// its declaring class and the entry with the same index is taken from this class.
+    pub fn select_virtual(&self, resolved: &ResolvedMethod) -> Selected {
+        // Methods resolved from superinterfaces, e.g. default methods, are only found in the itable
+        if resolved.class.is_interface() {
+            return self.select_interface(resolved);
+        }
+
+        match resolved.class.vtable().iter().position(|m| m.is_same(resolved)) {
+            Some(index) => Selected::Method(self.vtable()[index].clone()),
+            None => Selected::Method(resolved.clone())
+        }
+    }
+
+    /// Method selection for an interface method, the receiver is an instance of this class which
+    /// must implement the interface declaring the resolved method.
+    pub fn select_interface(&self, resolved: &ResolvedMethod) -> Selected {
+        let itable = self.itable.get().expect("class must be linked before use");
+
+        match itable.get(&resolved.class.name) {
+            Some(methods) => methods[resolved.index].clone(),
+            None => Selected::Missing
+        }
+    }
+
+    fn layout_fields(class: &Class, super_class: &Option<Rc<RuntimeClass>>) -> Vec<FieldSlot> {
+        let mut layout = Vec::new();
+
+        if let Some(super_class) = super_class {
+            layout.extend(super_class.field_layout.iter().cloned());
+        }
+
+        for field in class.fields.iter().filter(|f| !f.is_static()) {
+            layout.push(FieldSlot {
+                class_name: class.name.clone(),
+                name: field.name.clone(),
+                descriptor: field.descriptor.clone(),
+            });
+        }
+
+        layout
+    }
+
+    // Preparation, see https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-5.html#jvms-5.4.2
+    fn prepare_statics(class: &Class) -> Result<Vec<JTypeValue>> {
+        let mut values = Vec::new();
+
+        for field in class.fields.iter() {
+            if !field.is_static() {
+                values.push(JTypeValue::Empty);
+                continue;
+            }
+
+            let value = match field.attribute("ConstantValue") {
+                Some(attr) => {
+                    let index = u16::from_be_bytes([attr.data[0], attr.data[1]]);
+                    match class.const_pool.resolve(index as usize)? {
+                        Const::Integer(x) => JTypeValue::Int(*x),
+                        Const::Long(x) => JTypeValue::Long(*x),
+                        Const::Float(x) => JTypeValue::Float(*x),
+                        Const::Double(x) => JTypeValue::Double(*x),
+                        // String constants need a java/lang/String object, they are set once the class is initialized
+                        _ => JTypeValue::default_for(&field.descriptor),
+                    }
+                },
+                None => JTypeValue::default_for(&field.descriptor),
+            };
+
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    /// The static fields holding String constants, by their index, together with the text of the constant.
+    pub fn string_constants(&self) -> Result<Vec<(usize, Rc<str>)>> {
+        let mut constants = Vec::new();
+
+        for (index, field) in self.fields.iter().enumerate() {
+            if !field.is_static() {
+                continue;
+            }
+
+            if let Some(attr) = field.attribute("ConstantValue") {
+                let const_index = u16::from_be_bytes([attr.data[0], attr.data[1]]) as usize;
+                if let Const::StringIndex(_) = self.const_pool.resolve(const_index)? {
+                    constants.push((index, self.const_pool.resolve_string(const_index)?));
+                }
+            }
+        }
+
+        Ok(constants)
+    }
+
+    /// Field resolution, see https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-5.html#jvms-5.4.3.2
+    /// Returns the declaring class together with the index of the field within it.
+    pub fn resolve_field(self: &Rc<Self>, name: &str, desc: &str) -> Option<(Rc<RuntimeClass>, usize)> {
+        let declared = self.fields.iter()
+            .position(|f| f.name.as_ref() == name && f.descriptor.as_ref() == desc);
+
+        if let Some(index) = declared {
+            return Some((self.clone(), index));
+        }
+
+        for interface in self.interfaces.iter() {
+            if let Some(found) = interface.resolve_field(name, desc) {
+                return Some(found);
+            }
+        }
+
+        match &self.super_class {
+            Some(super_class) => super_class.resolve_field(name, desc),
+            None => None
+        }
+    }
+
+    /// Method resolution, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.3
+    pub fn resolve_method(self: &Rc<Self>, name: &str, desc: &str) -> Result<ResolvedMethod> {
+        if self.is_interface() {
+            let message = format!("Found interface {}, but class was expected", self.name.replace('/', "."));
+            return Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into());
+        }
+
+        if let Some(index) = self.find_signature_polymorphic(name) {
+            return Ok(ResolvedMethod { class: self.clone(), index });
+        }
+
+        let mut class = Some(self.clone());
+        while let Some(c) = class {
+            if let Some(index) = c.find_method(name, desc) {
+                return Ok(ResolvedMethod { class: c, index });
+            }
+            class = c.super_class.clone();
+        }
+
+        let candidates = self.superinterface_methods(name, desc);
+
+        let maximally_specific = Self::maximally_specific(&candidates);
+        let mut non_abstract = maximally_specific.iter().filter(|m| !m.method().is_abstract());
+        if let (Some(m), None) = (non_abstract.next(), non_abstract.next()) {
+            return Ok(m.clone());
+        }
+
+        // Any of the remaining superinterface methods can be chosen, see the JVMS
+        match candidates.into_iter().next() {
+            Some(m) => Ok(m),
+            None => Err(JavaException::new(NO_SUCH_METHOD_ERROR, method_signature(&self.name, name, desc)).into())
+        }
+    }
+
+    // Signature polymorphic methods match any descriptor, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-2.html#jvms-2.9
+    fn find_signature_polymorphic(&self, name: &str) -> Option<usize> {
+        if !matches!(self.name.as_ref(), "java/lang/invoke/MethodHandle" | "java/lang/invoke/VarHandle") {
+            return None;
+        }
+
+        let mut methods = self.methods.iter().enumerate().filter(|(_, m)| m.name.as_ref() == name);
+        match (methods.next(), methods.next()) {
+            (Some((index, m)), None) if m.is_native() && m.is_varargs() && m.descriptor.starts_with("([Ljava/lang/Object;)") => Some(index),
+            _ => None
+        }
+    }
+
+    /// Interface method resolution, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.4
+    pub fn resolve_interface_method(self: &Rc<Self>, name: &str, desc: &str) -> Result<ResolvedMethod> {
+        if !self.is_interface() {
+            let message = format!("Found class {}, but interface was expected", self.name.replace('/', "."));
+            return Err(JavaException::new(INCOMPATIBLE_CLASS_CHANGE_ERROR, message).into());
+        }
+
+        if let Some(index) = self.find_method(name, desc) {
+            return Ok(ResolvedMethod { class: self.clone(), index });
+        }
+
+        // The super class of an interface is always java/lang/Object
+        if let Some(object) = &self.super_class {
+            if let Some(index) = object.find_method(name, desc) {
+                let method = &object.methods[index];
+                if method.is_public() && !method.is_static() {
+                    return Ok(ResolvedMethod { class: object.clone(), index });
+                }
+            }
+        }
+
+        let candidates = self.superinterface_methods(name, desc);
+
+        let maximally_specific = Self::maximally_specific(&candidates);
+        let mut non_abstract = maximally_specific.iter().filter(|m| !m.method().is_abstract());
+        if let (Some(m), None) = (non_abstract.next(), non_abstract.next()) {
+            return Ok(m.clone());
+        }
+
+        match candidates.into_iter().next() {
+            Some(m) => Ok(m),
+            None => Err(JavaException::new(NO_SUCH_METHOD_ERROR, method_signature(&self.name, name, desc)).into())
+        }
+    }
+
+    /// Non-private, non-static methods with the given name and descriptor declared by any superinterface.
+    pub fn superinterface_methods(&self, name: &str, desc: &str) -> Vec<ResolvedMethod> {
+        self.superinterfaces().into_iter()
+            .filter_map(|i| i.find_method(name, desc).map(|index| ResolvedMethod { class: i, index }))
+            .filter(|m| !m.method().is_private() && !m.method().is_static())
+            .collect()
+    }
+
+    /// Keeps only the methods not overridden by another one declared in a subinterface, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.3
+    pub fn maximally_specific(methods: &[ResolvedMethod]) -> Vec<ResolvedMethod> {
+        methods.iter()
+            .filter(|m| !methods.iter().any(|other| other.class.name != m.class.name && other.class.is_subtype_of(&m.class.name)))
+            .cloned()
+            .collect()
+    }
+
+    /// All direct and indirect superinterfaces, including those of super classes.
+    pub fn superinterfaces(&self) -> Vec<Rc<RuntimeClass>> {
+        let mut result: Vec<Rc<RuntimeClass>> = Vec::new();
+
+        let mut pending: Vec<Rc<RuntimeClass>> = self.interfaces.clone();
+        let mut super_class = self.super_class.clone();
+        while let Some(c) = super_class {
+            pending.extend(c.interfaces.iter().cloned());
+            super_class = c.super_class.clone();
+        }
+
+        // Breadth first, so interfaces keep their declaration order
+        while !pending.is_empty() {
+            let interface = pending.remove(0);
+            if result.iter().any(|i| i.name == interface.name) {
+                continue;
+            }
+            pending.extend(interface.interfaces.iter().cloned());
+            result.push(interface);
+        }
+
+        result
+    }
+
+    /// Whether this class is the given class, or extends or implements it directly or indirectly.
+    pub fn is_subtype_of(&self, name: &str) -> bool {
+        if self.name.as_ref() == name {
+            return true;
+        }
+
+        if self.interfaces.iter().any(|i| i.is_subtype_of(name)) {
+            return true;
+        }
+
+        match &self.super_class {
+            Some(super_class) => super_class.is_subtype_of(name),
+            None => false
+        }
+    }
+
+    /// Offset of an instance field declared by this class within the objects of this class and its subclasses.
+    pub fn field_offset(&self, index: usize) -> Option<usize> {
+        let field = &self.fields[index];
+
+        self.field_layout.iter().position(|slot| slot.class_name == self.name
+            && slot.name == field.name
+            && slot.descriptor == field.descriptor)
+    }
+
+    /// Offset of the instance field with the given name and descriptor, declared by this class or a super class.
+    pub fn instance_field_offset(self: &Rc<Self>, name: &str, desc: &str) -> Option<usize> {
+        self.resolve_field(name, desc)
+            .and_then(|(declaring_class, index)| declaring_class.field_offset(index))
+    }
+
+    pub fn get_static(&self, index: usize) -> JTypeValue {
+        self.static_values.borrow()[index]
+    }
+
+    pub fn set_static(&self, index: usize, value: JTypeValue) {
+        self.static_values.borrow_mut()[index] = value;
+    }
+
+    /// The value of an already resolved constant, or the error its resolution failed with.
+    pub fn resolved_constant(&self, index: usize) -> Option<Result<JTypeValue>> {
+        self.resolved_constants.borrow().get(&index)
+            .map(|resolved| resolved.clone().map_err(|e| e.into()))
+    }
+
+    pub fn set_resolved_constant(&self, index: usize, resolved: std::result::Result<JTypeValue, ResolutionError>) {
+        self.resolved_constants.borrow_mut().insert(index, resolved);
+    }
+
+    pub fn call_site(&self, method_index: usize, pc: usize) -> Option<Rc<CallSite>> {
+        self.call_sites.borrow().get(&(method_index, pc)).cloned()
+    }
+
+    pub fn set_call_site(&self, method_index: usize, pc: usize, call_site: Rc<CallSite>) {
+        self.call_sites.borrow_mut().insert((method_index, pc), call_site);
+    }
+}
+
+#[derive(Debug)]
+pub struct MethodArea {
+    class_path: Vec<PathBuf>,
+    classes: HashMap<Rc<str>, Rc<RuntimeClass>>,
+    lambda_count: usize,
+    // java/lang/Class objects by the name of the class or primitive type they stand for
+    mirrors: HashMap<Rc<str>, usize>,
+}
+
+impl MethodArea {
+    pub fn new(class_path: Vec<PathBuf>) -> Self {
+        Self { class_path, classes: HashMap::new(), lambda_count: 0, mirrors: HashMap::new() }
+    }
+
+    /// Loads, links and prepares the class together with all its super classes and interfaces.
+    pub fn load_class(&mut self, name: &str) -> Result<Rc<RuntimeClass>> {
+        if let Some(c) = self.classes.get(name) {
+            return Ok(c.clone());
+        }
+
+        let class = self.read_class(name)?;
+        self.define_class(class, None)
+    }
+
+    /// Defines a class generated by the VM for a lambda, named after the class it is created in
+    /// the way HotSpot names them, e.g. Foo$$Lambda$1.
+    pub fn define_lambda_class(&mut self, mut class: Class, target: MethodHandleRef) -> Result<Rc<RuntimeClass>> {
+        self.lambda_count += 1;
+        class.name = format!("{}$$Lambda${}", class.name, self.lambda_count).into();
+
+        self.define_class(class, Some(target))
+    }
+
+    pub fn mirror(&self, name: &str) -> Option<usize> {
+        self.mirrors.get(name).copied()
+    }
+
+    pub fn set_mirror(&mut self, name: &str, mirror_ref: usize) {
+        self.mirrors.insert(name.into(), mirror_ref);
+    }
+
+    fn define_class(&mut self, class: Class, lambda_target: Option<MethodHandleRef>) -> Result<Rc<RuntimeClass>> {
+        let super_class = match &class.super_class {
+            Some(super_name) => Some(self.load_class(super_name)?),
+            None => None
+        };
+
+        let mut interfaces = Vec::new();
+        for interface in class.interfaces.iter() {
+            interfaces.push(self.load_class(interface)?);
+        }
+
+        let mut runtime_class = RuntimeClass::new(class, super_class, interfaces)?;
+        runtime_class.lambda_target = lambda_target;
+
+        let runtime_class = Rc::new(runtime_class);
+        runtime_class.link()?;
+        self.classes.insert(runtime_class.name.clone(), runtime_class.clone());
+
+        Ok(runtime_class)
+    }
+
+    fn read_class(&self, name: &str) -> Result<Class> {
+        for dir in self.class_path.iter() {
+            let path = dir.join(format!("{}.class", name));
+            if path.is_file() {
+                return crate::class::load(&path.to_string_lossy());
+            }
+        }
+
+        Err(JavaException::new(NO_CLASS_DEF_FOUND_ERROR, name.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_constant_value_applied_at_preparation() -> Result<()> {
+        let mut method_area = MethodArea::new(vec!["java".into(), "rt".into()]);
+        let class = method_area.load_class("Statics")?;
+
+        let (declaring, index) = class.resolve_field("ANSWER", "I").unwrap();
+        assert_eq!(declaring.get_static(index), JTypeValue::Int(42));
+
+        let (_, index) = class.resolve_field("BIG", "J").unwrap();
+        assert_eq!(class.get_static(index), JTypeValue::Long(1 << 40));
+
+        let (_, index) = class.resolve_field("counter", "I").unwrap();
+        assert_eq!(class.get_static(index), JTypeValue::Int(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,418 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use anyhow::{Result, anyhow};
+use crate::class::{MethodHandleRef, StaticMethod, FieldRef, REF_GET_FIELD, REF_GET_STATIC, REF_PUT_FIELD, REF_PUT_STATIC, REF_INVOKE_VIRTUAL,
+                   REF_INVOKE_STATIC, REF_INVOKE_SPECIAL, REF_NEW_INVOKE_SPECIAL, REF_INVOKE_INTERFACE};
+use crate::jvm::JThread;
+use crate::jvm::method_area::{ResolvedMethod, RuntimeClass};
+use crate::jvm::objects::Object;
+use crate::jvm::types::{JTypeValue, param_descriptors, return_descriptor};
+use crate::jvm::exceptions::{JavaException, method_type_name, NULL_POINTER_EXCEPTION, NO_SUCH_METHOD_ERROR, CLASS_CAST_EXCEPTION,
+                             WRONG_METHOD_TYPE_EXCEPTION};
+
+const METHOD_HANDLE_CLASS: &str = "java/lang/invoke/MethodHandle";
+const METHOD_TYPE_CLASS: &str = "java/lang/invoke/MethodType";
+
+// Reference types a boxed primitive can be converted to without knowing the exact wrapper
+const BOX_SUPERTYPES: [&str; 4] = ["Ljava/lang/Object;", "Ljava/lang/Number;", "Ljava/lang/Comparable;", "Ljava/io/Serializable;"];
+
+impl JThread {
+    /// Creates the java/lang/invoke/MethodHandle object a CONSTANT_MethodHandle resolves to, resolving the referenced
+    /// field or method first, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.5
+    pub fn new_method_handle(&mut self, handle: &MethodHandleRef) -> Result<JTypeValue> {
+        self.resolve_handle_member(handle)?;
+
+        let type_desc = Self::handle_type(handle);
+        let method_type = self.new_method_type(&type_desc)?;
+        let fields = [
+            ("type", "Ljava/lang/invoke/MethodType;", method_type),
+            ("referenceKind", "I", JTypeValue::Int(handle.kind as i32)),
+            ("owner", "Ljava/lang/String;", JTypeValue::Ref(self.new_string(&handle.class_name)?)),
+            ("name", "Ljava/lang/String;", JTypeValue::Ref(self.new_string(&handle.name)?)),
+            ("descriptor", "Ljava/lang/String;", JTypeValue::Ref(self.new_string(&handle.desc)?)),
+            ("isInterface", "Z", JTypeValue::Int(handle.is_interface as i32)),
+        ];
+
+        self.new_vm_object(METHOD_HANDLE_CLASS, &fields)
+    }
+
+    /// Resolves the field or method a method handle refers to.
+    pub fn resolve_handle_member(&mut self, handle: &MethodHandleRef) -> Result<()> {
+        match handle.kind {
+            REF_GET_STATIC | REF_PUT_STATIC => {
+                self.resolve_static_field(&Self::handle_field_ref(handle))?;
+            },
+            REF_GET_FIELD | REF_PUT_FIELD => {
+                self.resolve_instance_field(&Self::handle_field_ref(handle))?;
+            },
+            REF_NEW_INVOKE_SPECIAL => {
+                if handle.name.as_ref() != "<init>" {
+                    return Err(anyhow!("newInvokeSpecial method handle must refer to <init>, found {}", handle.name));
+                }
+                self.resolve_method(&Self::handle_method_ref(handle), false)?;
+            },
+            REF_INVOKE_STATIC => {
+                self.resolve_method(&Self::handle_method_ref(handle), true)?;
+            },
+            REF_INVOKE_VIRTUAL | REF_INVOKE_SPECIAL | REF_INVOKE_INTERFACE => {
+                self.resolve_method(&Self::handle_method_ref(handle), false)?;
+            },
+            kind => return Err(anyhow!("invalid method handle kind {}", kind))
+        }
+
+        Ok(())
+    }
+
+    /// The method type of a method handle, the receiver of instance fields and methods is the first parameter.
+    pub fn handle_type(handle: &MethodHandleRef) -> String {
+        let owner = format!("L{};", handle.class_name);
+
+        match handle.kind {
+            REF_GET_FIELD => format!("({}){}", owner, handle.desc),
+            REF_GET_STATIC => format!("(){}", handle.desc),
+            REF_PUT_FIELD => format!("({}{})V", owner, handle.desc),
+            REF_PUT_STATIC => format!("({})V", handle.desc),
+            REF_NEW_INVOKE_SPECIAL => format!("({}){}", param_descriptors(&handle.desc).concat(), owner),
+            REF_INVOKE_STATIC => handle.desc.to_string(),
+            _ => format!("({}{}", owner, &handle.desc[1..])
+        }
+    }
+
+    /// Creates the java/lang/invoke/MethodType object for a method descriptor.
+    pub fn new_method_type(&mut self, desc: &str) -> Result<JTypeValue> {
+        let descriptor = JTypeValue::Ref(self.new_string(desc)?);
+        self.new_vm_object(METHOD_TYPE_CLASS, &[("descriptor", "Ljava/lang/String;", descriptor)])
+    }
+
+    // Allocates an object of a runtime library class without running its constructor, setting the given fields
+    pub fn new_vm_object(&mut self, class_name: &str, fields: &[(&str, &str, JTypeValue)]) -> Result<JTypeValue> {
+        let class = self.load_class(class_name)?;
+        let mut object = Object::new(class.clone());
+
+        for (name, desc, value) in fields {
+            let offset = Self::vm_field_offset(&class, name, desc)?;
+            object.set_field(offset, *value);
+        }
+
+        Ok(JTypeValue::Ref(self.heap.borrow_mut().allocate_obj(object)))
+    }
+
+    /// Invokes MethodHandle.invokeExact or MethodHandle.invoke with the descriptor of the call site, the first
+    /// argument is the method handle itself, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.invokevirtual
+    pub fn invoke_polymorphic(&mut self, resolved: &ResolvedMethod, call_desc: &str, args: &[JTypeValue]) -> Result<JTypeValue> {
+        let handle_ref = match args[0] {
+            JTypeValue::Ref(r) => r,
+            v => return Err(anyhow!("{} called on {:?}", resolved.display_name(), v))
+        };
+        let (handle, type_desc) = self.read_method_handle(handle_ref)?;
+
+        match resolved.method().name.as_ref() {
+            "invokeExact" => {
+                if type_desc != call_desc {
+                    let message = format!("expected {} but found {}", method_type_name(&type_desc), method_type_name(call_desc));
+                    return Err(JavaException::new(WRONG_METHOD_TYPE_EXCEPTION, message).into());
+                }
+
+                self.invoke_method_handle(&handle, &args[1..])
+            },
+            "invoke" => self.invoke_with_conversions(&handle, &type_desc, call_desc, &args[1..]),
+            name => Err(anyhow!("signature polymorphic method {} is not supported", name))
+        }
+    }
+
+    /// Invokes a method handle of the given type with arguments and result converted from and to the types of the
+    /// call descriptor the way MethodHandle.asType does, see java.lang.invoke.MethodHandle#invoke
+    pub fn invoke_with_conversions(&mut self, handle: &MethodHandleRef, type_desc: &str, call_desc: &str, args: &[JTypeValue]) -> Result<JTypeValue> {
+        let handle_params = param_descriptors(type_desc);
+        let call_params = param_descriptors(call_desc);
+        let (handle_return, call_return) = (return_descriptor(type_desc), return_descriptor(call_desc));
+
+        let convertible = handle_params.len() == call_params.len()
+            && call_params.iter().zip(&handle_params).all(|(from, to)| Self::can_convert(from, to))
+            && (call_return == "V" || handle_return == "V" || Self::can_convert(handle_return, call_return));
+
+        if !convertible {
+            let message = format!("cannot convert MethodHandle{} to {}", method_type_name(type_desc), method_type_name(call_desc));
+            return Err(JavaException::new(WRONG_METHOD_TYPE_EXCEPTION, message).into());
+        }
+
+        let mut handle_args = Vec::new();
+        for ((value, from), to) in args.iter().zip(call_params).zip(handle_params) {
+            handle_args.push(self.adapt(*value, from, to)?);
+        }
+
+        let result = self.invoke_method_handle(handle, &handle_args)?;
+
+        match (handle_return, call_return) {
+            (_, "V") => Ok(JTypeValue::Empty),
+            ("V", to) => Ok(JTypeValue::default_for(to)),
+            (from, to) => self.adapt(result, from, to)
+        }
+    }
+
+    // Reads the symbolic reference and the type descriptor stored in a method handle object
+    fn read_method_handle(&self, handle_ref: usize) -> Result<(MethodHandleRef, String)> {
+        let (class, fields) = {
+            let heap = RefCell::borrow(&self.heap);
+            let handle = heap.get_obj(handle_ref)?;
+            (handle.class.clone(), handle.fields.clone())
+        };
+
+        let field = |name: &str, desc: &str| -> Result<JTypeValue> {
+            Ok(fields[Self::vm_field_offset(&class, name, desc)?])
+        };
+        let string_field = |name: &str| -> Result<String> {
+            match field(name, "Ljava/lang/String;")? {
+                JTypeValue::Ref(r) if r != 0 => self.read_string(r),
+                v => Err(anyhow!("MethodHandle.{} holds {:?}", name, v))
+            }
+        };
+
+        let type_desc = match field("type", "Ljava/lang/invoke/MethodType;")? {
+            JTypeValue::Ref(r) if r != 0 => {
+                let method_type = RefCell::borrow(&self.heap).get_obj(r)?.class.clone();
+                let offset = Self::vm_field_offset(&method_type, "descriptor", "Ljava/lang/String;")?;
+                match RefCell::borrow(&self.heap).get_obj(r)?.field_value(offset) {
+                    JTypeValue::Ref(s) if s != 0 => self.read_string(s)?,
+                    v => return Err(anyhow!("MethodType.descriptor holds {:?}", v))
+                }
+            },
+            v => return Err(anyhow!("MethodHandle.type holds {:?}", v))
+        };
+
+        let kind = match field("referenceKind", "I")? {
+            JTypeValue::Int(i) => i as u8,
+            v => return Err(anyhow!("MethodHandle.referenceKind holds {:?}", v))
+        };
+
+        let handle = MethodHandleRef {
+            kind,
+            class_name: string_field("owner")?.into(),
+            name: string_field("name")?.into(),
+            desc: string_field("descriptor")?.into(),
+            is_interface: field("isInterface", "Z")? == JTypeValue::Int(1),
+        };
+
+        Ok((handle, type_desc))
+    }
+
+    fn vm_field_offset(class: &Rc<RuntimeClass>, name: &str, desc: &str) -> Result<usize> {
+        class.instance_field_offset(name, desc)
+            .ok_or_else(|| anyhow!("{} has no {} {} field", class.name, desc, name))
+    }
+
+    /// Invokes the field or method a method handle refers to, the receiver (if any) is passed as the first argument.
+    /// Behaviour is the same as of the bytecode instruction of the handle kind, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.4.3.5
+    pub fn invoke_method_handle(&mut self, handle: &MethodHandleRef, args: &[JTypeValue]) -> Result<JTypeValue> {
+        let method_ref = Self::handle_method_ref(handle);
+
+        match handle.kind {
+            REF_GET_FIELD => {
+                let offset = self.resolve_instance_field(&Self::handle_field_ref(handle))?;
+                let obj_ref = Self::field_receiver(args[0])?;
+                Ok(RefCell::borrow(&self.heap).get_obj(obj_ref)?.field_value(offset))
+            },
+            REF_PUT_FIELD => {
+                let offset = self.resolve_instance_field(&Self::handle_field_ref(handle))?;
+                let obj_ref = Self::field_receiver(args[0])?;
+                self.heap.borrow_mut().get_obj_mut(obj_ref)?.set_field(offset, args[1]);
+                Ok(JTypeValue::Empty)
+            },
+            REF_GET_STATIC => {
+                let (class, index) = self.resolve_static_field(&Self::handle_field_ref(handle))?;
+                self.initialize_class(&class)?;
+                Ok(class.get_static(index))
+            },
+            REF_PUT_STATIC => {
+                let (class, index) = self.resolve_static_field(&Self::handle_field_ref(handle))?;
+                self.initialize_class(&class)?;
+                class.set_static(index, args[0]);
+                Ok(JTypeValue::Empty)
+            },
+            REF_INVOKE_STATIC => {
+                let method = self.resolve_method(&method_ref, true)?;
+                self.initialize_class(&method.class)?;
+                self.invoke(&method, args)
+            },
+            REF_INVOKE_VIRTUAL | REF_INVOKE_INTERFACE => {
+                let resolved = self.resolve_method(&method_ref, false)?;
+                let obj_ref = Self::receiver(&resolved, args[0])?;
+                let method = self.select_virtual(resolved, obj_ref)?;
+                self.invoke(&method, args)
+            },
+            REF_INVOKE_SPECIAL => {
+                let resolved = self.resolve_method(&method_ref, false)?;
+                Self::receiver(&resolved, args[0])?;
+
+                let class = self.load_class(&handle.class_name)?;
+                let selected = class.select_special(&handle.name, &handle.desc);
+                let method = Self::selected_method(selected, &resolved, &class)?;
+                self.invoke(&method, args)
+            },
+            REF_NEW_INVOKE_SPECIAL => {
+                let constructor = self.resolve_method(&method_ref, false)?;
+                if constructor.class.name != handle.class_name {
+                    return Err(JavaException::new(NO_SUCH_METHOD_ERROR, constructor.signature()).into());
+                }
+
+                self.initialize_class(&constructor.class)?;
+                let obj_ref = self.heap.borrow_mut().allocate_obj(Object::new(constructor.class.clone()));
+
+                let mut constructor_args = vec![JTypeValue::Ref(obj_ref)];
+                constructor_args.extend_from_slice(args);
+                self.invoke(&constructor, &constructor_args)?;
+
+                Ok(JTypeValue::Ref(obj_ref))
+            },
+            kind => Err(anyhow!("invalid method handle kind {}", kind))
+        }
+    }
+
+    fn handle_method_ref(handle: &MethodHandleRef) -> StaticMethod {
+        StaticMethod {
+            class_name: handle.class_name.clone(),
+            method_name: handle.name.clone(),
+            method_desc: handle.desc.clone(),
+            is_interface: handle.is_interface,
+        }
+    }
+
+    fn handle_field_ref(handle: &MethodHandleRef) -> FieldRef {
+        FieldRef {
+            class_name: handle.class_name.clone(),
+            field_name: handle.name.clone(),
+            field_desc: handle.desc.clone(),
+        }
+    }
+
+    fn receiver(resolved: &ResolvedMethod, value: JTypeValue) -> Result<usize> {
+        match value {
+            JTypeValue::Ref(0) => {
+                let message = format!("Cannot invoke \"{}\"", resolved.display_name());
+                Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into())
+            },
+            JTypeValue::Ref(r) => Ok(r),
+            v => Err(anyhow!("method handle receiver must be a reference, found {:?}", v))
+        }
+    }
+
+    // Field handles check the receiver with Objects.requireNonNull, so the exception has no message
+    fn field_receiver(value: JTypeValue) -> Result<usize> {
+        match value {
+            JTypeValue::Ref(0) => Err(JavaException::new(NULL_POINTER_EXCEPTION, String::new()).into()),
+            JTypeValue::Ref(r) => Ok(r),
+            v => Err(anyhow!("method handle receiver must be a reference, found {:?}", v))
+        }
+    }
+
+    // Whether MethodHandle.asType allows converting a value of one type to the other, see java.lang.invoke.MethodHandle#asType
+    fn can_convert(from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+
+        match (is_reference(from), is_reference(to)) {
+            (true, true) => true,
+            (false, false) => matches!((from, to),
+                ("B", "S") | ("B", "I") | ("B", "J") | ("B", "F") | ("B", "D")
+                | ("S", "I") | ("S", "J") | ("S", "F") | ("S", "D")
+                | ("C", "I") | ("C", "J") | ("C", "F") | ("C", "D")
+                | ("I", "J") | ("I", "F") | ("I", "D")
+                | ("J", "F") | ("J", "D")
+                | ("F", "D")),
+            (false, true) => match Self::wrapper_class(from) {
+                Ok((wrapper, _)) => to == format!("L{};", wrapper) || BOX_SUPERTYPES.contains(&to),
+                Err(_) => false
+            },
+            (true, false) => match Self::wrapper_class(to) {
+                Ok((wrapper, _)) => from == format!("L{};", wrapper) || BOX_SUPERTYPES.contains(&from),
+                Err(_) => false
+            }
+        }
+    }
+
+    /// Converts a value between the types of a call and the method it ends up invoking, boxing, unboxing,
+    /// widening or casting it.
+    pub fn adapt(&mut self, value: JTypeValue, from: &str, to: &str) -> Result<JTypeValue> {
+        match (is_reference(from), is_reference(to)) {
+            (false, true) => {
+                let (wrapper, _) = Self::wrapper_class(from)?;
+                let value_of = MethodHandleRef {
+                    kind: REF_INVOKE_STATIC,
+                    class_name: wrapper.into(),
+                    name: "valueOf".into(),
+                    desc: format!("({})L{};", from, wrapper).into(),
+                    is_interface: false,
+                };
+                self.invoke_method_handle(&value_of, &[value])
+            },
+            (true, false) => {
+                let (wrapper, unbox_method) = Self::wrapper_class(to)?;
+                match value {
+                    JTypeValue::Ref(0) => {
+                        let message = format!("Cannot invoke \"{}.{}()\"", wrapper.replace('/', "."), unbox_method);
+                        Err(JavaException::new(NULL_POINTER_EXCEPTION, message).into())
+                    },
+                    JTypeValue::Ref(r) => {
+                        self.check_cast(r, wrapper)?;
+                        self.invoke_virtual(r, unbox_method, &format!("(){}", to))
+                    },
+                    v => Err(anyhow!("cannot unbox {:?}", v))
+                }
+            },
+            (false, false) => Ok(match (value, to) {
+                (JTypeValue::Int(i), "J") => JTypeValue::Long(i as i64),
+                (JTypeValue::Int(i), "F") => JTypeValue::Float(i as f32),
+                (JTypeValue::Int(i), "D") => JTypeValue::Double(i as f64),
+                (JTypeValue::Long(l), "F") => JTypeValue::Float(l as f32),
+                (JTypeValue::Long(l), "D") => JTypeValue::Double(l as f64),
+                (JTypeValue::Float(f), "D") => JTypeValue::Double(f as f64),
+                (v, _) => v
+            }),
+            (true, true) => {
+                // Array descriptors double as class names, class types lose the L and ;
+                let class_name = to.strip_prefix('L').and_then(|t| t.strip_suffix(';')).unwrap_or(to);
+                if let JTypeValue::Ref(r) = value {
+                    if r != 0 {
+                        self.check_cast(r, class_name)?;
+                    }
+                }
+                Ok(value)
+            }
+        }
+    }
+
+    // Casts the way Class.cast does, which words its message differently from checkcast
+    fn check_cast(&self, obj_ref: usize, class_name: &str) -> Result<()> {
+        let object_class = RefCell::borrow(&self.heap).class_name(obj_ref)?;
+
+        if !self.is_assignable(&object_class, class_name)? {
+            let message = format!("Cannot cast {} to {}", object_class.replace('/', "."), class_name.replace('/', "."));
+            return Err(JavaException::new(CLASS_CAST_EXCEPTION, message).into());
+        }
+
+        Ok(())
+    }
+
+    // The wrapper class of a primitive type together with the method unboxing it
+    fn wrapper_class(desc: &str) -> Result<(&'static str, &'static str)> {
+        let wrapper = match desc {
+            "Z" => ("java/lang/Boolean", "booleanValue"),
+            "B" => ("java/lang/Byte", "byteValue"),
+            "C" => ("java/lang/Character", "charValue"),
+            "S" => ("java/lang/Short", "shortValue"),
+            "I" => ("java/lang/Integer", "intValue"),
+            "J" => ("java/lang/Long", "longValue"),
+            "F" => ("java/lang/Float", "floatValue"),
+            "D" => ("java/lang/Double", "doubleValue"),
+            _ => return Err(anyhow!("{} is not a primitive type", desc))
+        };
+
+        Ok(wrapper)
+    }
+}
+
+fn is_reference(desc: &str) -> bool {
+    desc.starts_with('L') || desc.starts_with('[')
+}
@@ -0,0 +1,49 @@
+use anyhow::{Result, anyhow};
+use crate::jvm::JThread;
+use crate::jvm::types::JTypeValue;
+
+pub const CLASS_CLASS: &str = "java/lang/Class";
+
+impl JThread {
+    /// Returns the java/lang/Class object of a class, interface or array type named the way CONSTANT_Class entries
+    /// name them, e.g. java/lang/String or [I. The class, or the element class of an array, is loaded first.
+    pub fn class_mirror(&mut self, class_name: &str) -> Result<usize> {
+        self.load_element_class(class_name)?;
+        self.mirror(class_name, &class_name.replace('/', "."))
+    }
+
+    /// Returns the java/lang/Class object of the type with the given field descriptor, e.g. int.class for I.
+    pub fn type_mirror(&mut self, desc: &str) -> Result<usize> {
+        let primitive = match desc.as_bytes()[0] {
+            b'L' => return self.class_mirror(&desc[1..desc.len() - 1]),
+            b'[' => return self.class_mirror(desc),
+            b'Z' => "boolean",
+            b'B' => "byte",
+            b'C' => "char",
+            b'S' => "short",
+            b'I' => "int",
+            b'J' => "long",
+            b'F' => "float",
+            b'D' => "double",
+            _ => "void"
+        };
+
+        self.mirror(primitive, primitive)
+    }
+
+    // There is one mirror per type, created on first use
+    fn mirror(&mut self, name: &str, java_name: &str) -> Result<usize> {
+        if let Some(mirror_ref) = self.method_area.borrow().mirror(name) {
+            return Ok(mirror_ref);
+        }
+
+        let java_name = JTypeValue::Ref(self.new_string(java_name)?);
+        let mirror_ref = match self.new_vm_object(CLASS_CLASS, &[("name", "Ljava/lang/String;", java_name)])? {
+            JTypeValue::Ref(r) => r,
+            v => return Err(anyhow!("new_vm_object returned {:?}", v))
+        };
+
+        self.method_area.borrow_mut().set_mirror(name, mirror_ref);
+        Ok(mirror_ref)
+    }
+}
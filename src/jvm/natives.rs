@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+use anyhow::{Result, anyhow};
+use crate::jvm::JThread;
+use crate::jvm::method_area::ResolvedMethod;
+use crate::jvm::types::JTypeValue;
+use crate::jvm::exceptions::{JavaException, UNSATISFIED_LINK_ERROR, NULL_POINTER_EXCEPTION, CLONE_NOT_SUPPORTED_EXCEPTION};
+
+const CLONEABLE_CLASS: &str = "java/lang/Cloneable";
+
+impl JThread {
+    /// Runs a native method implemented by the VM itself.
+    pub fn invoke_native(&mut self, method: &ResolvedMethod, args: &[JTypeValue]) -> Result<JTypeValue> {
+        if let Some(target) = &method.class.lambda_target {
+            return self.invoke_lambda(&target.clone(), method, args);
+        }
+
+        let name = &method.method().name;
+        let desc = &method.method().descriptor;
+
+        match (method.class.name.as_ref(), name.as_ref(), desc.as_ref()) {
+            // Objects never move, so the reference works as an identity hash code
+            ("java/lang/Object", "hashCode", "()I") => match args[0] {
+                JTypeValue::Ref(r) => Ok(JTypeValue::Int(r as i32)),
+                v => Err(anyhow!("hashCode called on {:?}", v))
+            },
+            ("java/lang/Object", "toString", "()Ljava/lang/String;") => {
+                let obj_ref = match args[0] {
+                    JTypeValue::Ref(r) => r,
+                    v => return Err(anyhow!("toString called on {:?}", v))
+                };
+
+                let hash_code = match self.invoke_virtual(obj_ref, "hashCode", "()I")? {
+                    JTypeValue::Int(i) => i,
+                    v => return Err(anyhow!("hashCode returned {:?}", v))
+                };
+
+                let class_name = RefCell::borrow(&self.heap).class_name(obj_ref)?.replace('/', ".");
+                let string_ref = self.new_string(&format!("{}@{:x}", class_name, hash_code))?;
+                Ok(JTypeValue::Ref(string_ref))
+            },
+            ("java/lang/Object", "clone", "()Ljava/lang/Object;") => match args[0] {
+                JTypeValue::Ref(r) => self.clone_object(r),
+                v => Err(anyhow!("clone called on {:?}", v))
+            },
+            ("java/lang/String", "valueOf", "(Ljava/lang/Object;)Ljava/lang/String;") => match args[0] {
+                JTypeValue::Ref(0) => Ok(JTypeValue::Ref(self.new_string("null")?)),
+                JTypeValue::Ref(r) => self.invoke_virtual(r, "toString", "()Ljava/lang/String;"),
+                v => Err(anyhow!("String.valueOf called with {:?}", v))
+            },
+            ("java/util/Objects", "requireNonNull", "(Ljava/lang/Object;)Ljava/lang/Object;") => match args[0] {
+                JTypeValue::Ref(0) => Err(JavaException::new(NULL_POINTER_EXCEPTION, String::new()).into()),
+                v => Ok(v)
+            },
+            _ => Err(JavaException::new(UNSATISFIED_LINK_ERROR, method.signature()).into())
+        }
+    }
+
+    // Arrays and objects of Cloneable classes are copied as they are, the copy refers to the same objects as the original
+    fn clone_object(&mut self, obj_ref: usize) -> Result<JTypeValue> {
+        let mut heap = self.heap.borrow_mut();
+
+        if let Some(array) = heap.arrays.get(&obj_ref) {
+            let copy = array.clone();
+            return Ok(JTypeValue::Ref(heap.allocate_arr(copy)));
+        }
+
+        let object = heap.get_obj(obj_ref)?;
+        if !object.class.is_subtype_of(CLONEABLE_CLASS) {
+            return Err(JavaException::new(CLONE_NOT_SUPPORTED_EXCEPTION, object.class.name.replace('/', ".")).into());
+        }
+
+        let copy = object.clone();
+        Ok(JTypeValue::Ref(heap.allocate_obj(copy)))
+    }
+}
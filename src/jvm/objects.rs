@@ -1,5 +1,6 @@
 use std::rc::Rc;
-use crate::class::Class;
+use anyhow::{Result, anyhow};
+use crate::jvm::method_area::RuntimeClass;
 use std::collections::HashMap;
 use crate::jvm::types::JTypeValue;
 
@@ -10,12 +11,16 @@ pub struct Heap {
     ref_counter: usize,
     pub objects: HashMap<usize, Object>,
     pub arrays: HashMap<usize, Array>,
+    // Interned strings by their text, see String.intern()
+    strings: HashMap<String, usize>,
+    // How many times the (only) thread entered the monitor of an object without exiting it
+    monitors: HashMap<usize, usize>,
 }
 
 impl Heap {
     pub fn new() -> Self {
         // Start reference counting from 1, 0 is considered NULL
-        Heap {ref_counter: 1, objects: HashMap::new(), arrays: HashMap::new() }
+        Heap {ref_counter: 1, objects: HashMap::new(), arrays: HashMap::new(), strings: HashMap::new(), monitors: HashMap::new() }
     }
 
     pub fn allocate_arr(&mut self, arr: Array) -> usize {
@@ -25,17 +30,17 @@ impl Heap {
         curr_ref
     }
 
-    pub fn get_arr(&self, arr_ref: usize) -> &Array {
+    pub fn get_arr(&self, arr_ref: usize) -> Result<&Array> {
         match self.arrays.get(&arr_ref) {
-            Some(arr) => arr,
-            None => panic!("array not found on the heap")
+            Some(arr) => Ok(arr),
+            None => Err(anyhow!("array {} not found on the heap", arr_ref))
         }
     }
 
-    pub fn get_arr_mut(&mut self, arr_ref: usize) -> &mut Array {
+    pub fn get_arr_mut(&mut self, arr_ref: usize) -> Result<&mut Array> {
         match self.arrays.get_mut(&arr_ref) {
-            Some(arr) => arr,
-            None => panic!("array not found on the heap!"),
+            Some(arr) => Ok(arr),
+            None => Err(anyhow!("array {} not found on the heap", arr_ref))
         }
     }
 
@@ -46,68 +51,194 @@ impl Heap {
         curr_ref
     }
 
-    pub fn get_obj(&self, obj_ref: usize) -> &Object {
+    pub fn get_obj(&self, obj_ref: usize) -> Result<&Object> {
         match self.objects.get(&obj_ref) {
-            Some(o) => o,
-            None => panic!("object not found on the heap")
+            Some(o) => Ok(o),
+            None => Err(anyhow!("object {} not found on the heap", obj_ref))
         }
     }
 
-    pub fn get_obj_mut(&mut self, obj_ref: usize) -> &mut Object {
+    pub fn get_obj_mut(&mut self, obj_ref: usize) -> Result<&mut Object> {
         match self.objects.get_mut(&obj_ref) {
-            Some(o) => o,
-            None => panic!("object not found on the heap")
+            Some(o) => Ok(o),
+            None => Err(anyhow!("object {} not found on the heap", obj_ref))
         }
     }
+
+    /// The name of the class of an object or array, e.g. java/lang/String or [I
+    pub fn class_name(&self, obj_ref: usize) -> Result<Rc<str>> {
+        match self.objects.get(&obj_ref) {
+            Some(o) => Ok(o.class.name.clone()),
+            None => Ok(self.get_arr(obj_ref)?.class_name())
+        }
+    }
+
+    pub fn enter_monitor(&mut self, obj_ref: usize) {
+        *self.monitors.entry(obj_ref).or_insert(0) += 1;
+    }
+
+    /// Exits the monitor of an object, returning false if the thread does not own it.
+    pub fn exit_monitor(&mut self, obj_ref: usize) -> bool {
+        match self.monitors.get_mut(&obj_ref) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            },
+            _ => false
+        }
+    }
+
+    pub fn interned_string(&self, text: &str) -> Option<usize> {
+        self.strings.get(text).copied()
+    }
+
+    pub fn intern_string(&mut self, text: &str, string_ref: usize) {
+        self.strings.insert(text.to_string(), string_ref);
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Object {
     // TODO how can we hide those fields?
-    pub class: Rc<Class>,
-    pub fields: HashMap<usize, JTypeValue>,
+    pub class: Rc<RuntimeClass>,
+    pub fields: Vec<JTypeValue>,
 }
 
 impl Object {
-    pub fn new(class: Rc<Class>) -> Self {
-        Object {class, fields: HashMap::new()}
+    pub fn new(class: Rc<RuntimeClass>) -> Self {
+        let fields = class.field_layout.iter()
+            .map(|slot| JTypeValue::default_for(&slot.descriptor))
+            .collect();
+
+        Object {class, fields}
     }
 
-    pub fn field_value(&self, index: usize) -> JTypeValue {
+    pub fn field_value(&self, offset: usize) -> JTypeValue {
+        self.fields[offset]
+    }
 
-        match self.fields.get(&index) {
-            Some(v) => *v,
-            None => panic!("field value not found")
-        }
+    pub fn set_field(&mut self, offset: usize, value: JTypeValue) {
+        self.fields[offset] = value;
     }
 }
 
+/// The elements of an array, stored compactly according to the component type.
+#[derive(Debug, Clone)]
+enum ArrayData {
+    Boolean(Vec<u8>),
+    Byte(Vec<i8>),
+    Char(Vec<u16>),
+    Short(Vec<i16>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    // Arrays of class, interface and array types keep their class name, e.g. [[Ljava/lang/String;
+    Reference(Rc<str>, Vec<usize>),
+}
+
+#[derive(Debug, Clone)]
 pub struct Array {
-    arr: Vec<JTypeValue>
+    data: ArrayData,
 }
 
 impl Array {
 
-    pub fn new(count: usize) -> Self {
-        Array { arr: vec![JTypeValue::Empty; count] }
+    /// Creates an array of the primitive type with the given descriptor, e.g. b'I' for int[], holding default values.
+    pub fn new_primitive(component: u8, count: usize) -> Result<Self> {
+        let data = match component {
+            b'Z' => ArrayData::Boolean(vec![0; count]),
+            b'B' => ArrayData::Byte(vec![0; count]),
+            b'C' => ArrayData::Char(vec![0; count]),
+            b'S' => ArrayData::Short(vec![0; count]),
+            b'I' => ArrayData::Int(vec![0; count]),
+            b'J' => ArrayData::Long(vec![0; count]),
+            b'F' => ArrayData::Float(vec![0.0; count]),
+            b'D' => ArrayData::Double(vec![0.0; count]),
+            c => return Err(anyhow!("{} is not a primitive type", c as char))
+        };
+
+        Ok(Array { data })
     }
 
-    pub fn set(&mut self, index: usize, val: JTypeValue) {
-        if index >= self.arr.len() {
-            panic!("trying to insert into array with ArrayIndexOutOfBoundsException");
-        }
+    /// Creates an array of a reference type holding nulls, the class name is that of the array itself, e.g.
+    /// [Ljava/lang/String; for String[].
+    pub fn new_reference(class_name: Rc<str>, count: usize) -> Self {
+        Array { data: ArrayData::Reference(class_name, vec![0; count]) }
+    }
 
-        self.arr[index] = val;
+    pub fn from_chars(chars: Vec<u16>) -> Self {
+        Array { data: ArrayData::Char(chars) }
     }
 
-    pub fn get_int(&self, index: usize) -> i32 {
-        match self.arr[index] {
-            JTypeValue::Int(i) => i,
-            _ => panic!("tried to get int from array but it is not an int")
+    /// The name of the array class, e.g. [I or [Ljava/lang/String;
+    pub fn class_name(&self) -> Rc<str> {
+        match &self.data {
+            ArrayData::Boolean(_) => "[Z".into(),
+            ArrayData::Byte(_) => "[B".into(),
+            ArrayData::Char(_) => "[C".into(),
+            ArrayData::Short(_) => "[S".into(),
+            ArrayData::Int(_) => "[I".into(),
+            ArrayData::Long(_) => "[J".into(),
+            ArrayData::Float(_) => "[F".into(),
+            ArrayData::Double(_) => "[D".into(),
+            ArrayData::Reference(class_name, _) => class_name.clone(),
         }
     }
 
+    /// Returns the element at the index, booleans, bytes, chars and shorts are widened to ints.
+    pub fn get(&self, index: usize) -> JTypeValue {
+        match &self.data {
+            ArrayData::Boolean(a) => JTypeValue::Int(a[index] as i32),
+            ArrayData::Byte(a) => JTypeValue::Int(a[index] as i32),
+            ArrayData::Char(a) => JTypeValue::Int(a[index] as i32),
+            ArrayData::Short(a) => JTypeValue::Int(a[index] as i32),
+            ArrayData::Int(a) => JTypeValue::Int(a[index]),
+            ArrayData::Long(a) => JTypeValue::Long(a[index]),
+            ArrayData::Float(a) => JTypeValue::Float(a[index]),
+            ArrayData::Double(a) => JTypeValue::Double(a[index]),
+            ArrayData::Reference(_, a) => JTypeValue::Ref(a[index]),
+        }
+    }
 
+    /// Stores the element at the index, ints stored to smaller types are truncated. Only the lowest bit of booleans
+    /// is kept, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.bastore
+    pub fn set(&mut self, index: usize, val: JTypeValue) -> Result<()> {
+        match (&mut self.data, val) {
+            (ArrayData::Boolean(a), JTypeValue::Int(i)) => a[index] = (i & 1) as u8,
+            (ArrayData::Byte(a), JTypeValue::Int(i)) => a[index] = i as i8,
+            (ArrayData::Char(a), JTypeValue::Int(i)) => a[index] = i as u16,
+            (ArrayData::Short(a), JTypeValue::Int(i)) => a[index] = i as i16,
+            (ArrayData::Int(a), JTypeValue::Int(i)) => a[index] = i,
+            (ArrayData::Long(a), JTypeValue::Long(l)) => a[index] = l,
+            (ArrayData::Float(a), JTypeValue::Float(f)) => a[index] = f,
+            (ArrayData::Double(a), JTypeValue::Double(d)) => a[index] = d,
+            (ArrayData::Reference(_, a), JTypeValue::Ref(r)) => a[index] = r,
+            (_, v) => return Err(anyhow!("cannot store {:?} to {}", v, self.class_name()))
+        }
 
-}
+        Ok(())
+    }
+
+    /// The characters of a char array.
+    pub fn chars(&self) -> Option<&[u16]> {
+        match &self.data {
+            ArrayData::Char(a) => Some(a),
+            _ => None
+        }
+    }
 
+    pub fn len(&self) -> usize {
+        match &self.data {
+            ArrayData::Boolean(a) => a.len(),
+            ArrayData::Byte(a) => a.len(),
+            ArrayData::Char(a) => a.len(),
+            ArrayData::Short(a) => a.len(),
+            ArrayData::Int(a) => a.len(),
+            ArrayData::Long(a) => a.len(),
+            ArrayData::Float(a) => a.len(),
+            ArrayData::Double(a) => a.len(),
+            ArrayData::Reference(_, a) => a.len(),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use anyhow::{Result, anyhow};
+use crate::jvm::JThread;
+use crate::jvm::method_area::RuntimeClass;
+use crate::jvm::objects::{Object, Array};
+use crate::jvm::types::JTypeValue;
+
+pub const STRING_CLASS: &str = "java/lang/String";
+
+impl JThread {
+    /// Allocates a java/lang/String holding the given text. The characters are stored as UTF-16 code units in
+    /// the `value` array, the constructor is not run.
+    pub fn new_string(&mut self, text: &str) -> Result<usize> {
+        let class = self.load_class(STRING_CLASS)?;
+        let offset = Self::string_value_offset(&class)?;
+
+        let value = Array::from_chars(text.encode_utf16().collect());
+
+        let mut heap = self.heap.borrow_mut();
+        let value_ref = heap.allocate_arr(value);
+
+        let mut string = Object::new(class);
+        string.set_field(offset, JTypeValue::Ref(value_ref));
+        Ok(heap.allocate_obj(string))
+    }
+
+    /// Returns the one java/lang/String shared by all string literals with the given text, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-5.html#jvms-5.1
+    pub fn intern_string(&mut self, text: &str) -> Result<usize> {
+        if let Some(string_ref) = RefCell::borrow(&self.heap).interned_string(text) {
+            return Ok(string_ref);
+        }
+
+        let string_ref = self.new_string(text)?;
+        self.heap.borrow_mut().intern_string(text, string_ref);
+        Ok(string_ref)
+    }
+
+    /// Reads the text of a java/lang/String object.
+    pub fn read_string(&self, string_ref: usize) -> Result<String> {
+        let heap = RefCell::borrow(&self.heap);
+        let string = heap.get_obj(string_ref)?;
+
+        if string.class.name.as_ref() != STRING_CLASS {
+            return Err(anyhow!("expected a {} but found a {}", STRING_CLASS, string.class.name));
+        }
+
+        let offset = Self::string_value_offset(&string.class)?;
+        let chars = match string.field_value(offset) {
+            JTypeValue::Ref(r) => heap.get_arr(r)?.chars(),
+            _ => None
+        };
+
+        match chars {
+            Some(chars) => Ok(String::from_utf16_lossy(chars)),
+            None => Err(anyhow!("String.value holds {:?} instead of a char array", string.field_value(offset)))
+        }
+    }
+
+    /// Converts a value of the type with the given descriptor to its text the way String.valueOf does.
+    /// Objects other than strings are converted by calling their toString() method.
+    pub fn string_value_of(&mut self, value: JTypeValue, desc: &str) -> Result<String> {
+        let text = match (value, desc.as_bytes()[0]) {
+            (JTypeValue::Int(i), b'Z') => (i != 0).to_string(),
+            (JTypeValue::Int(i), b'C') => String::from_utf16_lossy(&[i as u16]),
+            (JTypeValue::Int(i), _) => i.to_string(),
+            (JTypeValue::Long(l), _) => l.to_string(),
+            (JTypeValue::Float(f), _) => format_float(f),
+            (JTypeValue::Double(d), _) => format_double(d),
+            (JTypeValue::Ref(0), _) => "null".to_string(),
+            (JTypeValue::Ref(r), _) => {
+                let string_ref = match self.invoke_virtual(r, "toString", "()Ljava/lang/String;")? {
+                    JTypeValue::Ref(s) => s,
+                    v => return Err(anyhow!("toString() returned {:?}", v))
+                };
+
+                if string_ref == 0 {
+                    "null".to_string()
+                } else {
+                    self.read_string(string_ref)?
+                }
+            },
+            (v, _) => return Err(anyhow!("cannot convert {:?} to a string", v))
+        };
+
+        Ok(text)
+    }
+
+    fn string_value_offset(class: &Rc<RuntimeClass>) -> Result<usize> {
+        class.instance_field_offset("value", "[C")
+            .ok_or_else(|| anyhow!("{} has no char[] value field", STRING_CLASS))
+    }
+}
+
+/// Formats a double the way Double.toString does, e.g. 1.0, 0.001, 1.0E7 or 1.234E-5
+pub fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if d.is_infinite() {
+        return if d > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let magnitude = d.abs();
+    if d == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        with_fraction(d.to_string())
+    } else {
+        scientific(format!("{:e}", d))
+    }
+}
+
+/// Formats a float the way Float.toString does, with the shortest digits that identify the float
+pub fn format_float(f: f32) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if f.is_infinite() {
+        return if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let magnitude = f.abs();
+    if f == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        with_fraction(f.to_string())
+    } else {
+        scientific(format!("{:e}", f))
+    }
+}
+
+// Java always prints at least one digit after the decimal point
+fn with_fraction(plain: String) -> String {
+    if plain.contains('.') {
+        plain
+    } else {
+        plain + ".0"
+    }
+}
+
+// Turns Rust's exponent notation (1.5e-7, 1e10) into Java's (1.5E-7, 1.0E10)
+fn scientific(exponent_form: String) -> String {
+    let (mantissa, exponent) = match exponent_form.split_once('e') {
+        Some(parts) => parts,
+        None => return exponent_form
+    };
+
+    format!("{}E{}", with_fraction(mantissa.to_string()), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_format_double() {
+        assert_eq!(format_double(1.0), "1.0");
+        assert_eq!(format_double(-0.0), "-0.0");
+        assert_eq!(format_double(0.5), "0.5");
+        assert_eq!(format_double(0.001), "0.001");
+        assert_eq!(format_double(0.0001), "1.0E-4");
+        assert_eq!(format_double(1234567.25), "1234567.25");
+        assert_eq!(format_double(1e7), "1.0E7");
+        assert_eq!(format_double(-1.5e-7), "-1.5E-7");
+        assert_eq!(format_double(f64::NAN), "NaN");
+        assert_eq!(format_double(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(0.1), "0.1");
+        assert_eq!(format_float(2.5), "2.5");
+        assert_eq!(format_float(1e10), "1.0E10");
+        assert_eq!(format_float(3.4028235e38), "3.4028235E38");
+        assert_eq!(format_float(f32::INFINITY), "Infinity");
+    }
+}
@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+use anyhow::{Result, anyhow};
+use crate::jvm::JThread;
+use crate::jvm::exceptions::{JavaException, ThrownException, ERROR_CLASS};
+use crate::jvm::types::{JTypeValue, NULL_REF};
+
+impl JThread {
+    /// Wraps a Throwable object into the error unwinding the stack, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.athrow
+    pub fn throw(&self, exception_ref: usize) -> Result<ThrownException> {
+        let (class, message) = {
+            let heap = RefCell::borrow(&self.heap);
+            let exception = heap.get_obj(exception_ref)?;
+
+            let message = match exception.class.instance_field_offset("detailMessage", "Ljava/lang/String;") {
+                Some(offset) => exception.field_value(offset),
+                None => return Err(anyhow!("{} is not a java/lang/Throwable", exception.class.name))
+            };
+            (exception.class.clone(), message)
+        };
+
+        let message = match message {
+            JTypeValue::Ref(0) => None,
+            JTypeValue::Ref(r) => Some(self.read_string(r)?),
+            v => return Err(anyhow!("Throwable.detailMessage holds {:?}", v))
+        };
+
+        Ok(ThrownException { exception: JTypeValue::Ref(exception_ref), class_name: class.name.to_string(), message })
+    }
+
+    /// Creates the object of an exception raised by the VM itself, together with its causes. Like for strings, the
+    /// constructor is not run.
+    pub fn new_exception(&mut self, exception: &JavaException) -> Result<JTypeValue> {
+        let message = match exception.message.as_str() {
+            "" => NULL_REF,
+            message => JTypeValue::Ref(self.new_string(message)?)
+        };
+
+        let cause = match &exception.cause {
+            Some(cause) => self.new_exception(cause)?,
+            None => NULL_REF
+        };
+
+        self.new_vm_object(exception.class_name, &[
+            ("detailMessage", "Ljava/lang/String;", message),
+            ("cause", "Ljava/lang/Throwable;", cause),
+        ])
+    }
+
+    /// Creates an exception object of a class thrown by the VM around a Java object caught as its cause, e.g. a
+    /// BootstrapMethodError for what a bootstrap method threw.
+    pub fn wrap_thrown(&mut self, class_name: &str, message: &str, cause: &ThrownException) -> Result<ThrownException> {
+        let message = match message {
+            "" => NULL_REF,
+            message => JTypeValue::Ref(self.new_string(message)?)
+        };
+
+        let exception = self.new_vm_object(class_name, &[
+            ("detailMessage", "Ljava/lang/String;", message),
+            ("cause", "Ljava/lang/Throwable;", cause.exception),
+        ])?;
+
+        match exception {
+            JTypeValue::Ref(r) => self.throw(r),
+            v => Err(anyhow!("new_vm_object returned {:?}", v))
+        }
+    }
+
+    /// Whether the throwable class is java/lang/Error or one of its subclasses, which are passed on where exceptions
+    /// get wrapped, e.g. by bootstrap methods.
+    pub fn is_error(&self, class_name: &str) -> Result<bool> {
+        self.is_assignable(class_name, ERROR_CLASS)
+    }
+
+    /// Searches the exception table of the top frame's method for a handler of the exception covering the current
+    /// instruction, returning where the handler starts, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-2.html#jvms-2.10
+    /// When a catch type fails to resolve, the current instruction moves to its handler so that the search for the
+    /// resulting error continues after it, like in HotSpot.
+    pub fn find_handler(&mut self, exception_class: &str) -> Result<Option<usize>> {
+        let frame = match self.stack.last() {
+            Some(f) => f,
+            None => return Err(anyhow!("no frame to handle {}", exception_class))
+        };
+
+        let (class, method_index, ip) = (frame.class.clone(), frame.method_index, frame.ip);
+        for handler in class.methods[method_index].exception_table() {
+            if ip < handler.start_pc || ip >= handler.end_pc {
+                continue;
+            }
+
+            if handler.catch_type == 0 {
+                return Ok(Some(handler.handler_pc));
+            }
+
+            let catch_class = class.const_pool.resolve_class_name(handler.catch_type as usize)?;
+            let assignable = self.load_class(&catch_class)
+                .and_then(|_| self.is_assignable(exception_class, &catch_class));
+
+            match assignable {
+                Ok(true) => return Ok(Some(handler.handler_pc)),
+                Ok(false) => { },
+                Err(e) => {
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.ip = handler.handler_pc;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
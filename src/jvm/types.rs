@@ -1,4 +1,5 @@
-use std::ops::{Add, Neg};
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg, BitAnd, BitOr, BitXor, Shl, Shr};
+use anyhow::{Result, anyhow};
 
 pub const NULL_REF: JTypeValue = JTypeValue::Ref(0);
 
@@ -16,80 +17,257 @@ pub enum JTypeValue {
     Empty,
 }
 
+impl JTypeValue {
+    /// Initial value of a field with the given descriptor, see https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-2.html#jvms-2.3
+    pub fn default_for(desc: &str) -> Self {
+        match desc.as_bytes()[0] {
+            b'J' => JTypeValue::Long(0),
+            b'F' => JTypeValue::Float(0.0),
+            b'D' => JTypeValue::Double(0.0),
+            b'L' | b'[' => NULL_REF,
+            // boolean, byte, char, short and int are all represented as int
+            _ => JTypeValue::Int(0),
+        }
+    }
+}
+
+/// Splits the parameter part of a method descriptor into the descriptors of the single parameters,
+/// e.g. "(I[JLjava/lang/String;)V" into ["I", "[J", "Ljava/lang/String;"]
+pub fn param_descriptors(desc: &str) -> Vec<&str> {
+    let params = match desc[1..].split_once(')') {
+        Some((params, _)) => params,
+        None => "",
+    };
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    let bytes = params.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // array dimensions belong to the following element type
+            b'[' => {
+                i += 1;
+                continue;
+            },
+            // class names run until the terminating semicolon
+            b'L' => {
+                while bytes[i] != b';' {
+                    i += 1;
+                }
+            },
+            _ => {}
+        }
+
+        i += 1;
+        result.push(&params[start..i]);
+        start = i;
+    }
+    result
+}
+
+/// Returns the return type part of a method descriptor, e.g. "V" for "(I)V"
+pub fn return_descriptor(desc: &str) -> &str {
+    match desc.rfind(')') {
+        Some(i) => &desc[i + 1..],
+        None => "V"
+    }
+}
+
 impl From<i32> for JTypeValue {
     fn from(x: i32) -> Self {
-        return JTypeValue::Int(x);
+        JTypeValue::Int(x)
     }
 }
 
 impl Add<JTypeValue> for JTypeValue {
-    type Output = Self;
+    type Output = Result<Self>;
 
+    // Operands of the wrong type cannot come from verified code, so they are internal errors
     fn add(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_add(b))),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a.wrapping_add(b))),
+            (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a + b)),
+            (Self::Double(a), Self::Double(b)) => Ok(Self::Double(a + b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} + {:?}", a, b))
+        }
+    }
+}
 
-        // this function features panics but they are not expected to happen
-        // since Java compilation guarantees that the types will be correct
-        match self {
-            Self::Int(a) => match rhs {
-                Self::Int(b) => Self::Int(a + b),
-                _ => panic!("unsupported operation: adding int to non-int")
-            },
-            Self::Long(a) => match rhs {
-                Self::Long(b) => Self::Long(a + b),
-                _ => panic!("unsupported operation: adding long to non-long")
-            },
-            Self::Float(a) => match rhs {
-                Self::Float(b) => Self::Float(a + b),
-                _ => panic!("unsupported operation: adding float to non-float"),
-            },
-            Self::Double(a) => match rhs {
-                Self::Double(b) => Self::Double(a + b),
-                _ => panic!("unsuppported operation: adding double to non-double"),
-            }
+// Integer arithmetic wraps around on overflow like in Java, see https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-2.html#jvms-2.11.3
+impl Sub<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_sub(b))),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a.wrapping_sub(b))),
+            (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a - b)),
+            (Self::Double(a), Self::Double(b)) => Ok(Self::Double(a - b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} - {:?}", a, b))
+        }
+    }
+}
+
+impl Mul<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_mul(b))),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a.wrapping_mul(b))),
+            (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a * b)),
+            (Self::Double(a), Self::Double(b)) => Ok(Self::Double(a * b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} * {:?}", a, b))
+        }
+    }
+}
+
+// Integer division by zero has to be ruled out by the caller, dividing the smallest value by -1 overflows to itself.
+// Floating-point division follows IEEE 754, so dividing by zero gives an infinity or NaN
+impl Div<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_div(b))),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a.wrapping_div(b))),
+            (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a / b)),
+            (Self::Double(a), Self::Double(b)) => Ok(Self::Double(a / b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} / {:?}", a, b))
+        }
+    }
+}
+
+// The floating-point remainder truncates like C's fmod rather than rounding like the IEEE 754 remainder operation
+impl Rem<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_rem(b))),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a.wrapping_rem(b))),
+            (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a % b)),
+            (Self::Double(a), Self::Double(b)) => Ok(Self::Double(a % b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} % {:?}", a, b))
+        }
+    }
+}
+
+impl BitAnd<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a & b)),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a & b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} & {:?}", a, b))
+        }
+    }
+}
+
+impl BitOr<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a | b)),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a | b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} | {:?}", a, b))
+        }
+    }
+}
+
+impl BitXor<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a ^ b)),
+            (Self::Long(a), Self::Long(b)) => Ok(Self::Long(a ^ b)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} ^ {:?}", a, b))
+        }
+    }
+}
+
+// The shift distance is always an int, only its lowest 5 bits are used for ints and the lowest 6 bits for longs
+impl Shl<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
 
-            _ => panic!("unsupported operation!")
+    fn shl(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_shl(b as u32))),
+            (Self::Long(a), Self::Int(b)) => Ok(Self::Long(a.wrapping_shl(b as u32))),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} << {:?}", a, b))
         }
     }
 }
 
+// Arithmetic shift, the sign bit is extended
+impl Shr<JTypeValue> for JTypeValue {
+    type Output = Result<Self>;
+
+    fn shr(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a.wrapping_shr(b as u32))),
+            (Self::Long(a), Self::Int(b)) => Ok(Self::Long(a.wrapping_shr(b as u32))),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} >> {:?}", a, b))
+        }
+    }
+}
+
+impl JTypeValue {
+    /// Logical shift to the right (Java's >>>), zeros are shifted in.
+    pub fn ushr(self, rhs: Self) -> Result<Self> {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => Ok(Self::Int((a as u32).wrapping_shr(b as u32) as i32)),
+            (Self::Long(a), Self::Int(b)) => Ok(Self::Long((a as u64).wrapping_shr(b as u32) as i64)),
+            (a, b) => Err(anyhow!("unsupported operation: {:?} >>> {:?}", a, b))
+        }
+    }
+
+    /// Computational type category, longs and doubles are category 2 and take two words, see
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-2.html#jvms-2.11.1
+    pub fn category(&self) -> usize {
+        match self {
+            Self::Long(_) | Self::Double(_) => 2,
+            _ => 1
+        }
+    }
+
+    /// Whether the value is an integral zero, which integer division and remainder are not defined for.
+    pub fn is_zero_divisor(&self) -> bool {
+        matches!(self, Self::Int(0) | Self::Long(0))
+    }
+}
+
 impl Neg for JTypeValue {
-    type Output = Self;
+    type Output = Result<Self>;
 
     fn neg(self) -> Self::Output {
         match self {
-            Self::Int(a) => Self::Int(-a),
-            Self::Long(a) => Self::Long(-a),
-            Self::Float(a) => Self::Float(-a),
-            Self::Double(a) => Self::Double(-a),
-            _ => panic!("unsupported operation: cannot neg {:?}!", self)
+            Self::Int(a) => Ok(Self::Int(a.wrapping_neg())),
+            Self::Long(a) => Ok(Self::Long(a.wrapping_neg())),
+            Self::Float(a) => Ok(Self::Float(-a)),
+            Self::Double(a) => Ok(Self::Double(-a)),
+            a => Err(anyhow!("unsupported operation: -{:?}", a))
         }
     }
 }
 
+// Values of different types are never equal
 impl PartialEq for JTypeValue {
     fn eq(&self, other: &Self) -> bool {
-        match self {
-            Self::Int(a) => match other {
-                Self::Int(b) => a == b,
-                _ => panic!("unsupported operation, comparing int with non-int"),
-            },
-            Self::Long(a) => match other {
-                Self::Long(b) => a == b,
-                _ => panic!("unsupported operation: comparing long with non-long")
-            },
-            Self::Float(a) => match other {
-                Self::Float(b) => a == b,
-                _ => panic!("unsupported operation: comparing float with non-float"),
-            },
-            Self::Double(a) => match other {
-                Self::Double(b) => a == b,
-                _ => panic!("unsuppported operation: comparing double to non-double"),
-            },
-            Self::Ref(a) => match other {
-                Self::Ref(b) => a == b,
-                _ => panic!("unsuppported operation: comparing ref to non-ref"),
-            },
-            _ => panic!("unsupported operation")
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Long(a), Self::Long(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a == b,
+            (Self::Double(a), Self::Double(b)) => a == b,
+            (Self::Ref(a), Self::Ref(b)) => a == b,
+            (Self::RetAddr(a), Self::RetAddr(b)) => a == b,
+            (Self::Empty, Self::Empty) => true,
+            _ => false
         }
     }
 }
@@ -7,6 +7,8 @@ mod tests {
     use anyhow::Result;
     use crate::jvm::JTypeValue;
     use crate::jvm::JVM;
+    use crate::jvm::JavaException;
+    use crate::jvm::ThrownException;
 
     #[test]
     fn it_works() -> Result<()> {
@@ -17,9 +19,1073 @@ mod tests {
 
         match v {
             JTypeValue::Int(i) => assert_eq!(6, i),
-            _ => assert!(false)
+            _ => panic!("expected an int")
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn static_fields() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // <clinit> sets the counter to 10 before the first access
+        assert_eq!(jvm.run("Statics", "increment", &[])?, JTypeValue::Int(11));
+        assert_eq!(jvm.run("Statics", "increment", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Statics", "addToTotal", &[JTypeValue::Long(41)])?, JTypeValue::Long(42));
+        assert_eq!(jvm.run("Statics", "inherited", &[])?, JTypeValue::Int(7));
+        assert_eq!(jvm.run("Statics", "ratio", &[])?, JTypeValue::Double(0.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn instance_field_layout() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Fields", "sum", &[])?, JTypeValue::Int(7));
+        assert_eq!(jvm.run("Fields", "shadowed", &[])?, JTypeValue::Int(30));
+
+        assert_eq!(jvm.run("Fields", "defaultLong", &[])?, JTypeValue::Long(0));
+        assert_eq!(jvm.run("Fields", "defaultDouble", &[])?, JTypeValue::Double(0.0));
+        assert_eq!(jvm.run("Fields", "defaultFloat", &[])?, JTypeValue::Float(0.0));
+        assert_eq!(jvm.run("Fields", "defaultInts", &[])?, JTypeValue::Int(7));
+        assert_eq!(jvm.run("Fields", "defaultBoolean", &[])?, JTypeValue::Int(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn method_resolution() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Methods", "overloads", &[])?, JTypeValue::Int(15));
+        assert_eq!(jvm.run("Methods", "inheritedStatic", &[])?, JTypeValue::Int(5));
+        assert_eq!(jvm.run("Methods", "inheritedInstance", &[])?, JTypeValue::Int(6));
+        assert_eq!(jvm.run("Methods", "defaultMethod", &[])?, JTypeValue::Int(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn method_resolution_errors() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let err = jvm.run("LinkageRemoved", "callRemoved", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/NoSuchMethodError");
+        assert_eq!(exception.message, "'int LinkageBase.removed()'");
+
+        let err = jvm.run("Linkage", "callAbstract", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/AbstractMethodError");
+
+        Ok(())
+    }
+
+    #[test]
+    fn virtual_dispatch() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Virtual", "overridden", &[])?, JTypeValue::Int(2));
+        assert_eq!(jvm.run("Virtual", "inheritedOverride", &[])?, JTypeValue::Int(5));
+        assert_eq!(jvm.run("Virtual", "abstractMethod", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Virtual", "finalMethod", &[])?, JTypeValue::Int(7));
+
+        let err = jvm.run("Virtual", "nullReceiver", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/NullPointerException");
+        assert_eq!(exception.message, "Cannot invoke \"Animal.sound()\"");
+
+        Ok(())
+    }
+
+    #[test]
+    fn virtual_dispatch_package_private() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("VirtualPackages", "callAOnB", &[])?, JTypeValue::Int(1));
+        assert_eq!(jvm.run("VirtualPackages", "callAOnC", &[])?, JTypeValue::Int(3));
+        assert_eq!(jvm.run("VirtualPackages", "callBOnC", &[])?, JTypeValue::Int(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn overriding_final_method() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let err = jvm.run("FinalBase", "call", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/VerifyError");
+        assert_eq!(exception.message, "class FinalOverride overrides final method FinalBase.value()I");
+
+        Ok(())
+    }
+
+    #[test]
+    fn interface_dispatch() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Interfaces", "dispatch", &[])?, JTypeValue::Int(9));
+        assert_eq!(jvm.run("Interfaces", "defaultMethod", &[])?, JTypeValue::Int(109));
+        assert_eq!(jvm.run("Interfaces", "defaultMethodThroughClass", &[])?, JTypeValue::Int(109));
+        assert_eq!(jvm.run("Interfaces", "inheritedImplementation", &[])?, JTypeValue::Int(8));
+        assert_eq!(jvm.run("Interfaces", "maximallySpecific", &[])?, JTypeValue::Int(2));
+        assert_eq!(jvm.run("Interfaces", "staticMethod", &[])?, JTypeValue::Int(1));
+        assert_eq!(jvm.run("Interfaces", "privateMethod", &[])?, JTypeValue::Int(18));
+
+        Ok(())
+    }
+
+    #[test]
+    fn interface_dispatch_errors() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let err = jvm.run("DefaultConflicts", "callConflicting", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/IncompatibleClassChangeError");
+        assert_eq!(exception.message, "Conflicting default methods: DefaultLeft.side DefaultRight.side");
+
+        let err = jvm.run("DefaultConflicts", "callMissing", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/AbstractMethodError");
+        assert_eq!(exception.message, "Receiver class DefaultBoth does not define or inherit an implementation of the resolved method \
+                                       'abstract int DefaultRight.extra()' of interface DefaultRight.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn invokespecial() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Special", "constructorChain", &[])?, JTypeValue::Int(15));
+        assert_eq!(jvm.run("Special", "superCalls", &[])?, JTypeValue::Int(111));
+        assert_eq!(jvm.run("Special", "superCallToInheritedMethod", &[])?, JTypeValue::Int(99));
+        assert_eq!(jvm.run("Special", "interfaceSuperCall", &[])?, JTypeValue::Int(8));
+        assert_eq!(jvm.run("Special", "newAllocatesReferencedClass", &[])?, JTypeValue::Int(11));
+        assert_eq!(jvm.run("SpecialLegacy", "callPrivate", &[])?, JTypeValue::Int(42));
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_concat() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let result = jvm.run("Concat", "primitives", &[JTypeValue::Int(-7), JTypeValue::Long(1 << 40), JTypeValue::Float(0.1),
+            JTypeValue::Double(1e-5), JTypeValue::Int('x' as i32), JTypeValue::Int(1)])?;
+        assert_eq!(jvm.read_string(result)?, "i=-7 l=1099511627776 f=0.1 d=1.0E-5 c=x b=true");
+
+        let greeting = jvm.new_string("Hello")?;
+        let name = jvm.new_string("Zdzisław")?;
+        let result = jvm.run("Concat", "strings", &[greeting, name])?;
+        assert_eq!(jvm.read_string(result)?, "Hello, Zdzisław!");
+
+        let result = jvm.run("Concat", "objects", &[])?;
+        assert_eq!(jvm.read_string(result)?, "p=(3, 4) nothing=null");
+
+        // Object.toString() uses the identity hash code, which is the heap reference
+        let result = jvm.run("Concat", "identity", &[])?;
+        assert!(jvm.read_string(result)?.starts_with("Concat@"));
+
+        let result = jvm.run("Concat", "tags", &[JTypeValue::Int(5)])?;
+        assert_eq!(jvm.read_string(result)?, "\u{1}5\u{2}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn lambdas() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Lambdas", "runnable", &[])?, JTypeValue::Int(2));
+        assert_eq!(jvm.run("Lambdas", "capturing", &[JTypeValue::Int(30), JTypeValue::Int(12)])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("Lambdas", "comparator", &[])?, JTypeValue::Int(4));
+        assert_eq!(jvm.run("Lambdas", "virtualReference", &[])?, JTypeValue::Int(9));
+        assert_eq!(jvm.run("Lambdas", "boundReference", &[])?, JTypeValue::Int(25));
+        assert_eq!(jvm.run("Lambdas", "constructorReference", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Lambdas", "markerInterface", &[])?, JTypeValue::Int(40));
+        assert_eq!(jvm.run("Lambdas", "serializable", &[JTypeValue::Int(8)])?, JTypeValue::Int(7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn method_handles() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("HandleTests", "fields", &[])?, JTypeValue::Int(18));
+        assert_eq!(jvm.run("HandleTests", "statics", &[])?, JTypeValue::Int(14));
+        assert_eq!(jvm.run("HandleTests", "invokeVirtual", &[])?, JTypeValue::Int(2));
+        assert_eq!(jvm.run("HandleTests", "invokeSpecial", &[])?, JTypeValue::Int(1));
+        assert_eq!(jvm.run("HandleTests", "invokeStatic", &[])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("HandleTests", "newInvokeSpecial", &[])?, JTypeValue::Int(33));
+        assert_eq!(jvm.run("HandleTests", "invokeInterface", &[])?, JTypeValue::Int(4));
+        assert_eq!(jvm.run("HandleTests", "invokeAdapted", &[])?, JTypeValue::Int(42));
+
+        // Every ldc of the same constant gives the same handle
+        let handle = jvm.run("Handles", "handle", &[])?;
+        assert_eq!(jvm.run("Handles", "handle", &[])?, handle);
+
+        let handle_type = jvm.run("Handles", "handleType", &[])?;
+        assert_eq!(jvm.read_string(handle_type)?, "(II)I");
+
+        let method_type = jvm.run("Handles", "methodType", &[])?;
+        assert_eq!(jvm.read_string(method_type)?, "(ILjava/lang/String;)V");
+
+        Ok(())
+    }
+
+    #[test]
+    fn method_handle_type_errors() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let err = jvm.run("HandleTests", "wrongType", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/invoke/WrongMethodTypeException");
+        assert_eq!(exception.message, "expected (int,int)int but found (int,int)long");
+
+        let err = jvm.run("HandleTests", "invokeWrongArity", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/invoke/WrongMethodTypeException");
+        assert_eq!(exception.message, "cannot convert MethodHandle(int,int)int to (int,int,int)Object");
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_constants() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // The bootstrap method runs once, later loads of the constant use the first result
+        assert_eq!(jvm.run("Condy", "answer", &[])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("Condy", "answer", &[])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("Condy", "answerAgain", &[])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("CondyBootstraps", "calls", &[])?, JTypeValue::Int(1));
+
+        assert_eq!(jvm.run("Condy", "sum", &[])?, JTypeValue::Int(42));
+        assert_eq!(jvm.run("Condy", "nested", &[])?, JTypeValue::Int(42));
+
+        let name = jvm.run("Condy", "name", &[])?;
+        assert_eq!(jvm.read_string(name)?, "greeting");
+
+        // The lookup passed to the bootstrap method is that of the class loading the constant
+        let lookup_class = jvm.run("Condy", "lookupClass", &[])?;
+        assert_eq!(jvm.read_string(lookup_class)?, "Condy");
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_constant_errors() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // A failed resolution throws the same error every time without running the bootstrap method again
+        for _ in 0..2 {
+            let err = jvm.run("Condy", "failing", &[]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().unwrap();
+            assert_eq!(exception.class_name, "java/lang/BootstrapMethodError");
+            assert_eq!(exception.message, "bootstrap method initialization exception");
+            assert_eq!(exception.cause.as_ref().unwrap().class_name, "java/lang/NullPointerException");
+        }
+        assert_eq!(jvm.run("CondyBootstraps", "calls", &[])?, JTypeValue::Int(1));
+
+        // Errors are not wrapped
+        for _ in 0..2 {
+            let err = jvm.run("Condy", "missingClass", &[]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().unwrap();
+            assert_eq!(exception.class_name, "java/lang/NoClassDefFoundError");
+            assert_eq!(exception.message, "CondyMissing");
+        }
+        assert_eq!(jvm.run("CondyBootstraps", "calls", &[])?, JTypeValue::Int(2));
+
+        let err = jvm.run("Condy", "wrongType", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/BootstrapMethodError");
+        assert_eq!(exception.cause.as_ref().unwrap().class_name, "java/lang/ClassCastException");
+
+        // Exceptions thrown by the bootstrap method are wrapped too, whatever their name
+        for _ in 0..2 {
+            let err = jvm.run("Condy", "throwing", &[]).unwrap_err();
+            let thrown = err.downcast_ref::<ThrownException>().expect("expected a thrown exception");
+            assert_eq!(thrown.to_string(), "java.lang.BootstrapMethodError: bootstrap method initialization exception");
+
+            let cause = jvm.run("CondyBootstraps", "causeMessage", &[thrown.exception])?;
+            assert_eq!(jvm.read_string(cause)?, "no value");
+        }
+        assert_eq!(jvm.run("CondyBootstraps", "calls", &[])?, JTypeValue::Int(3));
+
+        // Only linkage errors are kept, like on HotSpot other errors run the bootstrap method again
+        for calls in 4..6 {
+            let err = jvm.run("Condy", "throwingError", &[]).unwrap_err();
+            let thrown = err.downcast_ref::<ThrownException>().expect("expected a thrown exception");
+            assert_eq!(thrown.to_string(), "java.lang.Error: fatal");
+            assert_eq!(jvm.run("CondyBootstraps", "calls", &[])?, JTypeValue::Int(calls));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_arithmetic() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("IntMath", "add", &[int(i32::MAX), int(1)])?, int(i32::MIN));
+        assert_eq!(jvm.run("IntMath", "sub", &[int(i32::MIN), int(1)])?, int(i32::MAX));
+        assert_eq!(jvm.run("IntMath", "sub", &[int(3), int(5)])?, int(-2));
+        assert_eq!(jvm.run("IntMath", "mul", &[int(65536), int(65536)])?, int(0));
+        assert_eq!(jvm.run("IntMath", "mul", &[int(-7), int(6)])?, int(-42));
+        assert_eq!(jvm.run("IntMath", "neg", &[int(i32::MIN)])?, int(i32::MIN));
+
+        // Division rounds towards zero, the remainder takes the sign of the dividend
+        assert_eq!(jvm.run("IntMath", "div", &[int(-7), int(2)])?, int(-3));
+        assert_eq!(jvm.run("IntMath", "rem", &[int(-7), int(2)])?, int(-1));
+        assert_eq!(jvm.run("IntMath", "rem", &[int(7), int(-2)])?, int(1));
+        assert_eq!(jvm.run("IntMath", "div", &[int(i32::MIN), int(-1)])?, int(i32::MIN));
+        assert_eq!(jvm.run("IntMath", "rem", &[int(i32::MIN), int(-1)])?, int(0));
+
+        // Only the lowest 5 bits of the shift distance are used
+        assert_eq!(jvm.run("IntMath", "shl", &[int(1), int(31)])?, int(i32::MIN));
+        assert_eq!(jvm.run("IntMath", "shl", &[int(1), int(33)])?, int(2));
+        assert_eq!(jvm.run("IntMath", "shl", &[int(1), int(-1)])?, int(i32::MIN));
+        assert_eq!(jvm.run("IntMath", "shr", &[int(-16), int(2)])?, int(-4));
+        assert_eq!(jvm.run("IntMath", "shr", &[int(-16), int(32)])?, int(-16));
+        assert_eq!(jvm.run("IntMath", "ushr", &[int(-16), int(28)])?, int(15));
+        assert_eq!(jvm.run("IntMath", "ushr", &[int(-1), int(0)])?, int(-1));
+
+        assert_eq!(jvm.run("IntMath", "and", &[int(0b1100), int(0b1010)])?, int(0b1000));
+        assert_eq!(jvm.run("IntMath", "or", &[int(0b1100), int(0b1010)])?, int(0b1110));
+        assert_eq!(jvm.run("IntMath", "xor", &[int(0b1100), int(0b1010)])?, int(0b0110));
+        assert_eq!(jvm.run("IntMath", "xor", &[int(-1), int(i32::MAX)])?, int(i32::MIN));
+
+        assert_eq!(jvm.run("IntMath", "inc", &[int(1)])?, int(102));
+        assert_eq!(jvm.run("IntMath", "inc", &[int(i32::MAX)])?, int(i32::MIN + 100));
+        assert_eq!(jvm.run("IntMath", "dec", &[int(0)])?, int(-128));
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_division_by_zero() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        for method in ["div", "rem"] {
+            let err = jvm.run("IntMath", method, &[JTypeValue::Int(1), JTypeValue::Int(0)]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().unwrap();
+            assert_eq!(exception.class_name, "java/lang/ArithmeticException");
+            assert_eq!(exception.message, "/ by zero");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn long_arithmetic() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let (int, long) = (JTypeValue::Int, JTypeValue::Long);
+
+        assert_eq!(jvm.run("LongMath", "add", &[long(i64::MAX), long(1)])?, long(i64::MIN));
+        assert_eq!(jvm.run("LongMath", "sub", &[long(i64::MIN), long(1)])?, long(i64::MAX));
+        assert_eq!(jvm.run("LongMath", "mul", &[long(1 << 32), long(1 << 32)])?, long(0));
+        assert_eq!(jvm.run("LongMath", "mul", &[long(-3_000_000_000), long(3)])?, long(-9_000_000_000));
+        assert_eq!(jvm.run("LongMath", "neg", &[long(i64::MIN)])?, long(i64::MIN));
+
+        assert_eq!(jvm.run("LongMath", "div", &[long(-7), long(2)])?, long(-3));
+        assert_eq!(jvm.run("LongMath", "rem", &[long(-7), long(2)])?, long(-1));
+        assert_eq!(jvm.run("LongMath", "div", &[long(i64::MIN), long(-1)])?, long(i64::MIN));
+        assert_eq!(jvm.run("LongMath", "rem", &[long(i64::MIN), long(-1)])?, long(0));
+
+        // Only the lowest 6 bits of the shift distance are used
+        assert_eq!(jvm.run("LongMath", "shl", &[long(1), int(63)])?, long(i64::MIN));
+        assert_eq!(jvm.run("LongMath", "shl", &[long(1), int(32)])?, long(1 << 32));
+        assert_eq!(jvm.run("LongMath", "shl", &[long(1), int(65)])?, long(2));
+        assert_eq!(jvm.run("LongMath", "shr", &[long(-16), int(2)])?, long(-4));
+        assert_eq!(jvm.run("LongMath", "shr", &[long(-16), int(64)])?, long(-16));
+        assert_eq!(jvm.run("LongMath", "ushr", &[long(-16), int(60)])?, long(15));
+        assert_eq!(jvm.run("LongMath", "ushr", &[long(-1), int(-1)])?, long(1));
+        assert_eq!(jvm.run("LongMath", "shiftAndAdd", &[long(3), int(40), long(5)])?, long((3 << 40) + 5));
+
+        assert_eq!(jvm.run("LongMath", "and", &[long(0b1100), long(0b1010)])?, long(0b1000));
+        assert_eq!(jvm.run("LongMath", "or", &[long(0b1100), long(0b1010)])?, long(0b1110));
+        assert_eq!(jvm.run("LongMath", "xor", &[long(-1), long(i64::MAX)])?, long(i64::MIN));
+
+        assert_eq!(jvm.run("Compare", "lcmp", &[long(1), long(2)])?, int(-1));
+        assert_eq!(jvm.run("Compare", "lcmp", &[long(2), long(2)])?, int(0));
+        assert_eq!(jvm.run("Compare", "lcmp", &[long(i64::MAX), long(i64::MIN)])?, int(1));
+
+        for method in ["div", "rem"] {
+            let err = jvm.run("LongMath", method, &[long(1), long(0)]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().unwrap();
+            assert_eq!(exception.class_name, "java/lang/ArithmeticException");
+            assert_eq!(exception.message, "/ by zero");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn floating_point_arithmetic() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let (float, double) = (JTypeValue::Float, JTypeValue::Double);
+
+        assert_eq!(jvm.run("FloatMath", "fadd", &[float(0.1), float(0.2)])?, float(0.1 + 0.2));
+        assert_eq!(jvm.run("FloatMath", "fsub", &[float(1.5), float(4.0)])?, float(-2.5));
+        assert_eq!(jvm.run("FloatMath", "fmul", &[float(f32::MAX), float(2.0)])?, float(f32::INFINITY));
+        assert_eq!(jvm.run("FloatMath", "fdiv", &[float(1.0), float(0.0)])?, float(f32::INFINITY));
+        assert_eq!(jvm.run("FloatMath", "fdiv", &[float(1.0), float(-0.0)])?, float(f32::NEG_INFINITY));
+        assert_eq!(jvm.run("FloatMath", "dadd", &[double(0.1), double(0.2)])?, double(0.30000000000000004));
+        assert_eq!(jvm.run("FloatMath", "dsub", &[double(1.0), double(1e-17)])?, double(1.0));
+        assert_eq!(jvm.run("FloatMath", "dmul", &[double(-2.5), double(4.0)])?, double(-10.0));
+        assert_eq!(jvm.run("FloatMath", "ddiv", &[double(-1.0), double(0.0)])?, double(f64::NEG_INFINITY));
+
+        // The remainder truncates, so it takes the sign of the dividend unlike the IEEE 754 remainder
+        assert_eq!(jvm.run("FloatMath", "frem", &[float(5.5), float(2.0)])?, float(1.5));
+        assert_eq!(jvm.run("FloatMath", "frem", &[float(-5.5), float(2.0)])?, float(-1.5));
+        assert_eq!(jvm.run("FloatMath", "drem", &[double(7.0), double(4.0)])?, double(3.0));
+        assert_eq!(jvm.run("FloatMath", "drem", &[double(5.0), double(f64::INFINITY)])?, double(5.0));
+
+        let is_nan = |value| matches!(value, JTypeValue::Float(f) if f.is_nan()) || matches!(value, JTypeValue::Double(d) if d.is_nan());
+        assert!(is_nan(jvm.run("FloatMath", "fdiv", &[float(0.0), float(0.0)])?));
+        assert!(is_nan(jvm.run("FloatMath", "frem", &[float(1.0), float(0.0)])?));
+        assert!(is_nan(jvm.run("FloatMath", "dmul", &[double(f64::INFINITY), double(0.0)])?));
+        assert!(is_nan(jvm.run("FloatMath", "drem", &[double(f64::INFINITY), double(2.0)])?));
+
+        // Negating flips the sign bit, also of zero
+        match jvm.run("FloatMath", "dneg", &[double(0.0)])? {
+            JTypeValue::Double(d) => assert!(d == 0.0 && d.is_sign_negative()),
+            v => panic!("expected a double but got {:?}", v)
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn floating_point_comparisons() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let (int, float, double) = (JTypeValue::Int, JTypeValue::Float, JTypeValue::Double);
+
+        for method in ["fcmpl", "fcmpg"] {
+            assert_eq!(jvm.run("Compare", method, &[float(1.0), float(2.0)])?, int(-1));
+            assert_eq!(jvm.run("Compare", method, &[float(2.0), float(1.0)])?, int(1));
+            assert_eq!(jvm.run("Compare", method, &[float(0.0), float(-0.0)])?, int(0));
+            assert_eq!(jvm.run("Compare", method, &[float(f32::NEG_INFINITY), float(f32::MIN)])?, int(-1));
+        }
+
+        for method in ["dcmpl", "dcmpg"] {
+            assert_eq!(jvm.run("Compare", method, &[double(1.0), double(2.0)])?, int(-1));
+            assert_eq!(jvm.run("Compare", method, &[double(2.0), double(1.0)])?, int(1));
+            assert_eq!(jvm.run("Compare", method, &[double(-0.0), double(0.0)])?, int(0));
+        }
+
+        // Any comparison with NaN is unordered
+        assert_eq!(jvm.run("Compare", "fcmpl", &[float(f32::NAN), float(1.0)])?, int(-1));
+        assert_eq!(jvm.run("Compare", "fcmpg", &[float(1.0), float(f32::NAN)])?, int(1));
+        assert_eq!(jvm.run("Compare", "dcmpl", &[double(f64::NAN), double(f64::NAN)])?, int(-1));
+        assert_eq!(jvm.run("Compare", "dcmpg", &[double(f64::NAN), double(f64::NAN)])?, int(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn numeric_conversions() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let (int, long, float, double) = (JTypeValue::Int, JTypeValue::Long, JTypeValue::Float, JTypeValue::Double);
+
+        assert_eq!(jvm.run("Conversions", "i2l", &[int(-5)])?, long(-5));
+        assert_eq!(jvm.run("Conversions", "i2f", &[int(16_777_217)])?, float(16_777_216.0));
+        assert_eq!(jvm.run("Conversions", "i2d", &[int(i32::MIN)])?, double(-2147483648.0));
+        assert_eq!(jvm.run("Conversions", "l2i", &[long(0x1_0000_0005)])?, int(5));
+        assert_eq!(jvm.run("Conversions", "l2i", &[long(0xFFFF_FFFF)])?, int(-1));
+        assert_eq!(jvm.run("Conversions", "l2d", &[long(1 << 53)])?, double(9007199254740992.0));
+
+        // Rounds to the nearest float, ties to even
+        assert_eq!(jvm.run("Conversions", "l2f", &[long(16_777_219)])?, float(16_777_220.0));
+        assert_eq!(jvm.run("Conversions", "l2f", &[long(i64::MAX)])?, float(9.223372E18));
+
+        // Floating-point values are rounded towards zero and saturate, NaN becomes 0
+        assert_eq!(jvm.run("Conversions", "f2i", &[float(-2.9)])?, int(-2));
+        assert_eq!(jvm.run("Conversions", "f2i", &[float(1e20)])?, int(i32::MAX));
+        assert_eq!(jvm.run("Conversions", "f2i", &[float(f32::NEG_INFINITY)])?, int(i32::MIN));
+        assert_eq!(jvm.run("Conversions", "f2i", &[float(f32::NAN)])?, int(0));
+        assert_eq!(jvm.run("Conversions", "f2l", &[float(1e20)])?, long(i64::MAX));
+        assert_eq!(jvm.run("Conversions", "f2l", &[float(f32::NAN)])?, long(0));
+        assert_eq!(jvm.run("Conversions", "d2i", &[double(2.9)])?, int(2));
+        assert_eq!(jvm.run("Conversions", "d2i", &[double(-1e10)])?, int(i32::MIN));
+        assert_eq!(jvm.run("Conversions", "d2i", &[double(f64::NAN)])?, int(0));
+        assert_eq!(jvm.run("Conversions", "d2l", &[double(-1e30)])?, long(i64::MIN));
+        assert_eq!(jvm.run("Conversions", "d2l", &[double(f64::INFINITY)])?, long(i64::MAX));
+
+        assert_eq!(jvm.run("Conversions", "f2d", &[float(0.1)])?, double(0.10000000149011612));
+        assert_eq!(jvm.run("Conversions", "d2f", &[double(0.1)])?, float(0.1));
+        assert_eq!(jvm.run("Conversions", "d2f", &[double(1e40)])?, float(f32::INFINITY));
+        assert_eq!(jvm.run("Conversions", "d2f", &[double(1e-50)])?, float(0.0));
+
+        // byte and short are sign-extended, char is zero-extended
+        assert_eq!(jvm.run("Conversions", "i2b", &[int(200)])?, int(-56));
+        assert_eq!(jvm.run("Conversions", "i2b", &[int(0x17F)])?, int(127));
+        assert_eq!(jvm.run("Conversions", "i2c", &[int(-1)])?, int(65535));
+        assert_eq!(jvm.run("Conversions", "i2c", &[int(0x1_0041)])?, int(65));
+        assert_eq!(jvm.run("Conversions", "i2s", &[int(40000)])?, int(-25536));
+
+        assert_eq!(jvm.run("Conversions", "mixed", &[int(3), long(4), float(1.0), double(4.0)])?, double(12.25));
+
+        Ok(())
+    }
+
+    #[test]
+    fn branches() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("Add", "doStuff", &[])?, int(10));
+
+        assert_eq!(jvm.run("Branches", "sumTo", &[int(10)])?, int(55));
+        assert_eq!(jvm.run("Branches", "sumTo", &[int(0)])?, int(0));
+        assert_eq!(jvm.run("Branches", "countDown", &[int(5)])?, int(5));
+        assert_eq!(jvm.run("Branches", "countDown", &[int(-5)])?, int(1));
+        assert_eq!(jvm.run("Branches", "nested", &[int(6)])?, int(6));
+        assert_eq!(jvm.run("Branches", "max", &[JTypeValue::Long(3), JTypeValue::Long(2)])?, int(1));
+        assert_eq!(jvm.run("Branches", "classify", &[JTypeValue::Double(-0.5)])?, int(-1));
+        assert_eq!(jvm.run("Branches", "classify", &[JTypeValue::Double(f64::NAN)])?, int(0));
+        assert_eq!(jvm.run("Branches", "isNull", &[JTypeValue::Ref(0)])?, int(1));
+
+        assert_eq!(jvm.run("WideJumps", "sumTo", &[int(10)])?, int(55));
+
+        Ok(())
+    }
+
+    #[test]
+    fn switches() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        let dense = [(-2, 0), (-1, 10), (0, 20), (1, 30), (2, 30), (3, 0), (4, 40), (5, 0), (i32::MIN, 0)];
+        for (key, expected) in dense {
+            assert_eq!(jvm.run("Branches", "dense", &[int(key)])?, int(expected));
+        }
+
+        let sparse = [(-1000000, 1), (7, 2), (8, 0), (1000, 3), (i32::MAX, 4), (i32::MIN, 0)];
+        for (key, expected) in sparse {
+            assert_eq!(jvm.run("Branches", "sparse", &[int(key)])?, int(expected));
+        }
+
+        let fall_through = [(1, 3), (2, 2), (3, 3), (4, 0)];
+        for (key, expected) in fall_through {
+            assert_eq!(jvm.run("Branches", "fallThrough", &[int(key)])?, int(expected));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn subroutines() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("Subroutines", "finallyReturn", &[int(5)])?, int(10));
+        assert_eq!(jvm.run("Subroutines", "loop", &[int(5)])?, int(40));
+        assert_eq!(jvm.run("Subroutines", "nested", &[int(5)])?, int(8));
+
+        // Class files of version 50 and above must not contain subroutines
+        let err = jvm.run("SubroutinesModern", "finallyReturn", &[int(5)]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().unwrap();
+        assert_eq!(exception.class_name, "java/lang/VerifyError");
+        assert_eq!(exception.message, "Bad instruction: a8 in method 'int SubroutinesModern.finallyReturn(int)' of class file version 50");
+
+        Ok(())
+    }
+
+    #[test]
+    fn wide_local_indices() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("Wide", "ints", &[int(-7)])?, int(-7));
+        assert_eq!(jvm.run("Wide", "longs", &[JTypeValue::Long(i64::MIN)])?, JTypeValue::Long(i64::MIN));
+        assert_eq!(jvm.run("Wide", "floats", &[JTypeValue::Float(2.5)])?, JTypeValue::Float(2.5));
+        assert_eq!(jvm.run("Wide", "doubles", &[JTypeValue::Double(-0.25)])?, JTypeValue::Double(-0.25));
+
+        let string = jvm.new_string("wide")?;
+        assert_eq!(jvm.run("Wide", "refs", &[string])?, string);
+
+        // The increment is a signed 16-bit value
+        assert_eq!(jvm.run("Wide", "increment", &[int(1)])?, int(1000));
+        assert_eq!(jvm.run("Wide", "increment", &[int(i32::MAX)])?, int(i32::MIN + 998));
+        assert_eq!(jvm.run("Wide", "subroutine", &[int(41)])?, int(42));
+
+        Ok(())
+    }
+
+    #[test]
+    fn stack_manipulation() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // Digits left on the stack from the bottom to the top, see java/gen/GenStack.java
+        let forms = [
+            ("pop", 1), ("pop2Form1", 1), ("pop2Form2", 1),
+            ("dup", 122), ("dupX1", 1323), ("dupX2Form1", 14234), ("dupX2Form2", 1323),
+            ("dup2Form1", 12323), ("dup2Form2", 122),
+            ("dup2X1Form1", 134234), ("dup2X1Form2", 1323),
+            ("dup2X2Form1", 1452345), ("dup2X2Form2", 14234), ("dup2X2Form3", 134234), ("dup2X2Form4", 1323),
+            ("swap", 132),
+        ];
+
+        for (method, expected) in forms {
+            assert_eq!(jvm.run("Stack", method, &[])?, JTypeValue::Int(expected), "{}", method);
+        }
+
+        assert_eq!(jvm.run("Counters", "fieldIncrement", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Counters", "longFieldIncrement", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Counters", "staticIncrement", &[])?, JTypeValue::Int(12));
+        assert_eq!(jvm.run("Counters", "arrayIncrement", &[JTypeValue::Int(4)])?, JTypeValue::Int(89));
+
+        Ok(())
+    }
+
+    #[test]
+    fn constants() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        assert_eq!(jvm.run("Constants", "negativeByte", &[])?, JTypeValue::Int(-100));
+        assert_eq!(jvm.run("Constants", "short1000", &[])?, JTypeValue::Int(1000));
+        assert_eq!(jvm.run("Constants", "negativeShort", &[])?, JTypeValue::Int(-30000));
+        assert_eq!(jvm.run("Constants", "bigLong", &[])?, JTypeValue::Long(1234567890123));
+        assert_eq!(jvm.run("Constants", "smallDouble", &[])?, JTypeValue::Double(1.5e-10));
+        assert_eq!(jvm.run("Constants", "mixed", &[JTypeValue::Long(2)])?, JTypeValue::Long(2000000214));
+        assert_eq!(jvm.run("WideConstants", "wideInt", &[])?, JTypeValue::Int(7654321));
+        assert_eq!(jvm.run("WideConstants", "wideFloat", &[])?, JTypeValue::Float(2.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_constants() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        let greeting = jvm.run("Constants", "greeting", &[])?;
+        assert_eq!(jvm.read_string(greeting)?, "hello");
+
+        let unicode = jvm.run("Constants", "unicode", &[])?;
+        assert_eq!(jvm.read_string(unicode)?, "zażółć ☺");
+
+        let nul_and_emoji = jvm.run("Constants", "nulAndEmoji", &[])?;
+        assert_eq!(jvm.read_string(nul_and_emoji)?, "a\0b\u{1F600}");
+
+        // Literals are interned, the same text gives the same string everywhere
+        assert_eq!(jvm.run("ConstantsOther", "greeting", &[])?, greeting);
+        assert_eq!(jvm.run("WideConstants", "wideString", &[])?, greeting);
+        assert_eq!(jvm.run("Constants", "sameLiteral", &[])?, JTypeValue::Int(1));
+
+        // So is the value of a constant String field
+        assert_eq!(jvm.run("StaticStrings", "greeting", &[])?, greeting);
+
+        Ok(())
+    }
+
+    #[test]
+    fn class_constants() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // There is one mirror per class
+        let string_class = jvm.run("Constants", "stringClass", &[])?;
+        assert_eq!(jvm.run("ConstantsOther", "stringClass", &[])?, string_class);
+        assert_eq!(jvm.run("WideConstants", "wideClass", &[])?, string_class);
+
+        let name = jvm.run("Constants", "className", &[])?;
+        assert_eq!(jvm.read_string(name)?, "Constants");
+
+        let name = jvm.run("Constants", "arrayClassName", &[])?;
+        assert_eq!(jvm.read_string(name)?, "[[Ljava.lang.String;");
+
+        let name = jvm.run("Constants", "primitiveArrayClassName", &[])?;
+        assert_eq!(jvm.read_string(name)?, "[I");
+
+        for _ in 0..2 {
+            let err = jvm.run("Constants", "missingClass", &[]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().unwrap();
+            assert_eq!(exception.class_name, "java/lang/NoClassDefFoundError");
+            assert_eq!(exception.message, "ConstantsMissing");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn primitive_arrays() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("PrimitiveArrays", "defaults", &[])?, int(0));
+        assert_eq!(jvm.run("PrimitiveArrays", "length", &[int(7)])?, int(7));
+        assert_eq!(jvm.run("PrimitiveArrays", "bytes", &[int(200)])?, int(-56));
+        assert_eq!(jvm.run("PrimitiveArrays", "chars", &[int(-1)])?, int(65535));
+        assert_eq!(jvm.run("PrimitiveArrays", "shorts", &[int(40000)])?, int(-25536));
+        assert_eq!(jvm.run("PrimitiveArrays", "booleans", &[int(1)])?, int(1));
+        assert_eq!(jvm.run("PrimitiveArrays", "longs", &[JTypeValue::Long(1 << 40)])?, JTypeValue::Long(3 << 40));
+        assert_eq!(jvm.run("PrimitiveArrays", "floats", &[JTypeValue::Float(5.0)])?, JTypeValue::Float(2.5));
+        assert_eq!(jvm.run("PrimitiveArrays", "doubles", &[JTypeValue::Double(0.1)])?, JTypeValue::Double(-0.1));
+        assert_eq!(jvm.run("PrimitiveArrays", "sum", &[int(5)])?, int(30));
+
+        let string = jvm.run("PrimitiveArrays", "fromChars", &[])?;
+        assert_eq!(jvm.read_string(string)?, "2hi");
+
+        // The store instructions truncate the int to the component type, booleans keep the lowest bit only
+        let stores = [
+            ("booleans", 2, 0), ("booleans", 3, 1), ("booleans", -1, 1),
+            ("bytes", 200, -56), ("bytes", 70000, 112),
+            ("chars", -1, 65535), ("chars", 70000, 4464),
+            ("shorts", 40000, -25536), ("shorts", 70000, 4464),
+        ];
+        for (method, value, expected) in stores {
+            assert_eq!(jvm.run("ArrayStores", method, &[int(value)])?, int(expected), "{}({})", method, value);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reference_arrays() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        let args = jvm.new_string_array(&["one", "two", "three"])?;
+        assert_eq!(jvm.run("ReferenceArrays", "count", &[args])?, int(3));
+        let last = jvm.run("ReferenceArrays", "last", &[args])?;
+        assert_eq!(jvm.read_string(last)?, "three");
+
+        let no_args = jvm.new_string_array(&[])?;
+        assert_eq!(jvm.run("ReferenceArrays", "count", &[no_args])?, int(0));
+
+        assert_eq!(jvm.run("ReferenceArrays", "defaults", &[int(4)])?, int(4));
+        let text = jvm.new_string("stored")?;
+        let stored = jvm.run("ReferenceArrays", "store", &[text])?;
+        assert_eq!(jvm.read_string(stored)?, "stored");
+
+        assert_eq!(jvm.run("ReferenceArrays", "grid", &[int(3), int(4)])?, int(4311));
+        assert_eq!(jvm.run("ReferenceArrays", "partial", &[])?, int(21));
+        let deep = jvm.run("ReferenceArrays", "jagged", &[])?;
+        assert_eq!(jvm.read_string(deep)?, "deep");
+        assert_eq!(jvm.run("ReferenceArrays", "covariant", &[])?, int(1));
+
+        assert_eq!(jvm.run("ReferenceArrays", "instances", &[])?, int(1011011));
+        assert_eq!(jvm.run("ReferenceArrays", "objects", &[text])?, int(101));
+        assert_eq!(jvm.run("ReferenceArrays", "objects", &[JTypeValue::Ref(0)])?, int(0));
+
+        let name = jvm.run("ReferenceArrays", "arrayClassName", &[])?;
+        assert_eq!(jvm.read_string(name)?, "[[Ljava.lang.String;");
+
+        // The message names the class of the stored value, like HotSpot's
+        let stores = [("storeInteger", "java.lang.Integer"), ("storeIntArray", "[I")];
+        for (method, message) in stores {
+            let err = jvm.run("ReferenceArrays", method, &[]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+            assert_eq!(exception.class_name, "java/lang/ArrayStoreException");
+            assert_eq!(exception.message, message);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_objects() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        // Arrays have the methods of java.lang.Object, the identity hash code is the reference
+        let strings = jvm.new_string_array(&["a", "b"])?;
+        let strings_ref = match strings {
+            JTypeValue::Ref(r) => r,
+            v => panic!("new_string_array returned {:?}", v)
+        };
+        assert_eq!(jvm.run("ArrayObjects", "hashCodeOf", &[strings])?, int(strings_ref as i32));
+
+        let text = jvm.run("ArrayObjects", "concat", &[strings])?;
+        assert_eq!(jvm.read_string(text)?, format!("[Ljava.lang.String;@{:x}", strings_ref));
+        let text = jvm.run("ArrayObjects", "toStringOf", &[strings])?;
+        assert_eq!(jvm.read_string(text)?, format!("[Ljava.lang.String;@{:x}", strings_ref));
+
+        // Clones are shallow copies, the expected values are what HotSpot gives
+        assert_eq!(jvm.run("ArrayObjects", "cloneInts", &[])?, int(3111));
+        assert_eq!(jvm.run("ArrayObjects", "cloneStrings", &[strings])?, int(2));
+        assert_eq!(jvm.run("ArrayObjects", "cloneGrid", &[])?, int(11));
+        assert_eq!(jvm.run("ArrayObjects", "clonePoint", &[])?, int(57));
+
+        let message = jvm.run("ArrayObjects", "cloneUncloneable", &[])?;
+        assert_eq!(jvm.read_string(message)?, "ArrayObjects");
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_checks() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        assert_eq!(jvm.run("ArrayChecks", "load", &[int(1)])?, int(0));
+        assert_eq!(jvm.run("ArrayChecks", "newInts", &[int(0)])?, int(0));
+        assert_eq!(jvm.run("ArrayChecks", "newGrid", &[int(0), int(3)])?, int(0));
+
+        let failures = [
+            ("load", vec![int(2)], "java/lang/ArrayIndexOutOfBoundsException", "Index 2 out of bounds for length 2"),
+            ("load", vec![int(-1)], "java/lang/ArrayIndexOutOfBoundsException", "Index -1 out of bounds for length 2"),
+            ("store", vec![int(5)], "java/lang/ArrayIndexOutOfBoundsException", "Index 5 out of bounds for length 2"),
+            ("storeOutOfBounds", vec![], "java/lang/ArrayIndexOutOfBoundsException", "Index 1 out of bounds for length 1"),
+            ("newInts", vec![int(-1)], "java/lang/NegativeArraySizeException", "-1"),
+            ("newStrings", vec![int(-3)], "java/lang/NegativeArraySizeException", "-3"),
+            ("newGrid", vec![int(2), int(-4)], "java/lang/NegativeArraySizeException", "-4"),
+            ("newGrid", vec![int(0), int(-4)], "java/lang/NegativeArraySizeException", "-4"),
+            ("loadNull", vec![], "java/lang/NullPointerException", "Cannot load from int array"),
+            ("storeNull", vec![], "java/lang/NullPointerException", "Cannot store to byte/boolean array"),
+            ("loadNullObject", vec![], "java/lang/NullPointerException", "Cannot load from object array"),
+            ("lengthNull", vec![], "java/lang/NullPointerException", "Cannot read the array length"),
+        ];
+        for (method, args, class_name, message) in failures {
+            let err = jvm.run("ArrayChecks", method, &args).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+            assert_eq!((exception.class_name, exception.message.as_str()), (class_name, message), "{}{:?}", method, args);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn exceptions() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        let message = jvm.run("Exceptions", "caught", &[])?;
+        assert_eq!(jvm.read_string(message)?, "boom");
+
+        assert_eq!(jvm.run("Exceptions", "subclass", &[int(0)])?, int(0));
+        assert_eq!(jvm.run("Exceptions", "subclass", &[int(1)])?, int(1));
+        assert_eq!(jvm.run("Exceptions", "subclass", &[int(2)])?, int(2));
+        assert_eq!(jvm.run("Exceptions", "subclass", &[int(3)])?, int(3));
+
+        assert_eq!(jvm.run("Exceptions", "operandStack", &[])?, int(101));
+        assert_eq!(jvm.run("Exceptions", "finallyBlocks", &[int(0)])?, int(1));
+        assert_eq!(jvm.run("Exceptions", "finallyBlocks", &[int(2)])?, int(11));
+
+        let message = jvm.run("Exceptions", "unwinding", &[int(20)])?;
+        assert_eq!(jvm.read_string(message)?, "bottom");
+        let cause = jvm.run("Exceptions", "rethrown", &[])?;
+        assert_eq!(jvm.read_string(cause)?, "inner");
+
+        // An uncaught exception unwinds the whole thread and carries the Java object out of run
+        assert_eq!(jvm.run("Exceptions", "uncaught", &[int(0)])?, int(0));
+        let err = jvm.run("Exceptions", "uncaught", &[int(7)]).unwrap_err();
+        let thrown = err.downcast_ref::<ThrownException>().expect("expected a thrown exception");
+        assert_eq!(thrown.class_name, "ExceptionsFailure");
+        assert_eq!(thrown.message.as_deref(), Some("value 7"));
+        assert_eq!(thrown.to_string(), "ExceptionsFailure: value 7");
+
+        let message = jvm.run("Exceptions", "messageOf", &[thrown.exception])?;
+        assert_eq!(jvm.read_string(message)?, "value 7");
+
+        let err = jvm.run("Exceptions", "withoutMessage", &[]).unwrap_err();
+        let thrown = err.downcast_ref::<ThrownException>().expect("expected a thrown exception");
+        assert_eq!((thrown.class_name.as_str(), thrown.message.as_deref()), ("java/lang/Error", None));
+
+        let err = jvm.run("Exceptions", "throwNull", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+        assert_eq!((exception.class_name, exception.message.as_str()), ("java/lang/NullPointerException", "Cannot throw exception"));
+
+        // A catch type that fails to resolve replaces the exception being handled, outer handlers still see it
+        let message = jvm.run("Exceptions", "missingCatchType", &[])?;
+        assert_eq!(jvm.read_string(message)?, "ExceptionsMissing");
+        let err = jvm.run("Exceptions", "catchMissing", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+        assert_eq!((exception.class_name, exception.message.as_str()), ("java/lang/NoClassDefFoundError", "ExceptionsMissing"));
+
+        // The thread is left usable
+        assert_eq!(jvm.run("Exceptions", "subclass", &[int(0)])?, int(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_class_initialization() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // The expected values are what HotSpot gives
+        assert_eq!(jvm.run("ClassInit", "clinitFails", &[])?, JTypeValue::Int(110));
+
+        let messages = [
+            ("thrownMessages", "no value, Could not initialize class ClassInitThrowing"),
+            ("errorMessage", "fatal"),
+            ("errorMessage", "Could not initialize class ClassInitError"),
+            ("childMessages", "no parent, Could not initialize class ClassInitChild"),
+        ];
+        for (method, expected) in messages {
+            let message = jvm.run("ClassInit", method, &[])?;
+            assert_eq!(jvm.read_string(message)?, expected, "{}", method);
+        }
+
+        let err = jvm.run("ClassInit", "uncaught", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+        assert_eq!(exception.to_string(), "java.lang.ExceptionInInitializerError");
+        assert_eq!(exception.cause.as_ref().unwrap().to_string(), "java.lang.ArithmeticException: / by zero");
+
+        let err = jvm.run("ClassInit", "uncaught", &[]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+        assert_eq!(exception.to_string(), "java.lang.NoClassDefFoundError: Could not initialize class ClassInitUncaught");
+
+        Ok(())
+    }
+
+    #[test]
+    fn implicit_exceptions() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        // Exceptions raised by the VM are caught by Java handlers as objects
+        let messages = [
+            ("divide", vec![int(1), int(0)], "/ by zero"),
+            ("readField", vec![], "Cannot read field \"x\""),
+            ("assignField", vec![], "Cannot assign field \"x\""),
+            ("invokeNull", vec![], "Cannot invoke \"ImplicitPoint.name()\""),
+            ("cast", vec![], "class java.lang.Integer cannot be cast to class java.lang.String (java.lang.Integer and java.lang.String are in module java.base of loader 'bootstrap')"),
+            ("arrayStore", vec![], "java.lang.Integer"),
+            ("arrayIndex", vec![int(3)], "Index 3 out of bounds for length 3"),
+            ("arraySize", vec![int(-2)], "-2"),
+            ("synchronizedNull", vec![], "Cannot enter synchronized block"),
+        ];
+        for (method, args, message) in messages {
+            let result = jvm.run("ImplicitExceptions", method, &args)?;
+            assert_eq!(jvm.read_string(result)?, message, "{}{:?}", method, args);
+        }
+
+        let result = jvm.run("ImplicitExceptions", "arrayIndex", &[int(2)])?;
+        assert_eq!(jvm.read_string(result)?, "zero");
+
+        let lock = jvm.new_string("lock")?;
+        assert_eq!(jvm.run("ImplicitExceptions", "synchronizedBlock", &[lock, int(5)])?, int(2));
+        assert_eq!(jvm.run("ImplicitExceptions", "synchronizedBlock", &[lock, int(0)])?, int(-1));
+
+        assert_eq!(jvm.run("Monitors", "catchUnowned", &[])?, int(1));
+        for method in ["exitUnowned", "exitTwice"] {
+            let err = jvm.run("Monitors", method, &[lock]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+            assert_eq!((exception.class_name, exception.message.as_str()), ("java/lang/IllegalMonitorStateException", ""));
+        }
+
+        // Uncaught, they reach the caller of run as they were raised, together with their Java object
+        let err = jvm.run("ImplicitExceptions", "uncaught", &[JTypeValue::Ref(0)]).unwrap_err();
+        let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+        assert_eq!((exception.class_name, exception.message.as_str()), ("java/lang/NullPointerException", "Cannot load from int array"));
+
+        let message = jvm.run("Exceptions", "messageOf", &[exception.exception.expect("expected the exception object")])?;
+        assert_eq!(jvm.read_string(message)?, "Cannot load from int array");
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_operands() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+
+        // Unverified code using operands of the wrong type fails with an internal error instead of a Java exception
+        for method in ["compareInts", "convertNull", "branchOnNull", "storeIntToObjects", "missingArgument",
+                       "missingDynamicArgument"] {
+            let err = jvm.run("BadOperands", method, &[]).unwrap_err();
+            assert!(err.downcast_ref::<JavaException>().is_none(), "{}: {}", method, err);
+        }
+
+        // The VM is left usable
+        assert_eq!(jvm.run("BadOperands", "add", &[JTypeValue::Int(1), JTypeValue::Int(2)])?, JTypeValue::Int(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn stack_overflow() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let overflow = jvm.run("ImplicitExceptions", "stackOverflow", &[])?;
+
+        // The frame of stackOverflow itself takes the last of the 16384 frames
+        assert_eq!(overflow, JTypeValue::Int(16_383));
+
+        // Calls through lambdas nest on the native stack, running out of it is caught the same way. How deep that is
+        // depends on the build
+        let overflow = jvm.run("ImplicitExceptions", "lambdaStackOverflow", &[])?;
+        assert!(matches!(overflow, JTypeValue::Int(depth) if depth > 0 && depth < 16_383), "{:?}", overflow);
+
+        Ok(())
+    }
+
+    #[test]
+    fn casts() -> Result<()> {
+
+        let mut jvm = JVM::new()?;
+        let int = JTypeValue::Int;
+
+        // null, CastsBase, CastsChild, CastsChild[], CastsBase[][], int[], int[][] and String, the expected values
+        // are what HotSpot gives
+        let instances = [0, 9, 15, 504, 1432, 904, 408, 2312];
+        let casts = [1023, 1, 7, 120, 584, 320, 72, 128];
+        for kind in 0..8 {
+            assert_eq!(jvm.run("Casts", "instances", &[int(kind)])?, int(instances[kind as usize]), "instances({})", kind);
+            assert_eq!(jvm.run("Casts", "casts", &[int(kind)])?, int(casts[kind as usize]), "casts({})", kind);
+        }
+
+        let messages = [
+            (1, 1, "class CastsBase cannot be cast to class CastsChild (CastsBase and CastsChild are in unnamed module of loader 'app')"),
+            (1, 2, "class CastsBase cannot be cast to class CastsTag (CastsBase and CastsTag are in unnamed module of loader 'app')"),
+            (2, 7, "class CastsChild cannot be cast to class java.lang.String (CastsChild is in unnamed module of loader 'app'; java.lang.String is in module java.base of loader 'bootstrap')"),
+            (3, 0, "class [LCastsChild; cannot be cast to class CastsBase ([LCastsChild; and CastsBase are in unnamed module of loader 'app')"),
+            (4, 5, "class [[LCastsBase; cannot be cast to class [LCastsChild; ([[LCastsBase; and [LCastsChild; are in unnamed module of loader 'app')"),
+            (5, 3, "class [I cannot be cast to class [Ljava.lang.Object; ([I and [Ljava.lang.Object; are in module java.base of loader 'bootstrap')"),
+            (5, 4, "class [I cannot be cast to class [LCastsBase; ([I is in module java.base of loader 'bootstrap'; [LCastsBase; is in unnamed module of loader 'app')"),
+            (6, 8, "class [[I cannot be cast to class [I ([[I and [I are in module java.base of loader 'bootstrap')"),
+            (7, 6, "class java.lang.String cannot be cast to class java.lang.Cloneable (java.lang.String and java.lang.Cloneable are in module java.base of loader 'bootstrap')"),
+        ];
+        for (kind, target, message) in messages {
+            let err = jvm.run("Casts", "cast", &[int(kind), int(target)]).unwrap_err();
+            let exception = err.downcast_ref::<JavaException>().expect("expected a Java exception");
+            assert_eq!((exception.class_name, exception.message.as_str()), ("java/lang/ClassCastException", message), "cast({}, {})", kind, target);
+        }
+
+        Ok(())
+    }
+}